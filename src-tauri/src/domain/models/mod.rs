@@ -1 +1,3 @@
-pub mod user;
+pub mod session;
+pub mod user;
+pub mod window_state;
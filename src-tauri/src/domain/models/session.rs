@@ -0,0 +1,26 @@
+use appdb::Id;
+use appdb::Store;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use surrealdb_types::SurrealValue;
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, SurrealValue)]
+pub struct SessionWindow {
+    pub label: String,
+    pub route: Option<String>,
+    pub params: HashMap<String, String>,
+    pub x: i64,
+    pub y: i64,
+    pub width: i64,
+    pub height: i64,
+    pub maximized: bool,
+    pub fullscreen: bool,
+    pub monitor: Option<String>,
+}
+
+/// The whole session is one record, so a checkpoint replaces it atomically.
+#[derive(Debug, Serialize, Deserialize, Clone, SurrealValue, Store)]
+pub struct Session {
+    pub id: Id,
+    pub windows: Vec<SessionWindow>,
+}
@@ -0,0 +1,17 @@
+use appdb::Id;
+use appdb::Store;
+use serde::{Deserialize, Serialize};
+use surrealdb_types::SurrealValue;
+
+#[derive(Debug, Serialize, Deserialize, Clone, SurrealValue, Store)]
+pub struct WindowState {
+    pub id: Id,
+    pub label: String,
+    pub x: i64,
+    pub y: i64,
+    pub width: i64,
+    pub height: i64,
+    pub maximized: bool,
+    pub fullscreen: bool,
+    pub monitor: Option<String>,
+}
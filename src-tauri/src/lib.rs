@@ -4,31 +4,72 @@ mod utils;
 use anyhow::Result;
 use appdb::prelude::{InitDbOptions, init_db_with_options};
 use domain::models::user::User;
+use std::time::Duration;
 use tauri::Manager;
 use tauri::async_runtime::block_on;
 use tauri_specta::{Builder, collect_commands, collect_events};
 use tokio::task::block_in_place;
+use utils::error::AppError;
 use utils::event;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    utils::window::install_window_descriptors(
+        utils::window_kinds::window_descriptors()
+            .build()
+            .expect("Failed to build window descriptors"),
+    )
+    .expect("Failed to install window descriptors");
+
+    let context = tauri::generate_context!();
+    #[cfg(desktop)]
+    let instance_listener = {
+        use utils::single_instance::{ForwardedLaunch, InstanceRole, claim_single_instance};
+
+        match claim_single_instance(&context.config().identifier, &ForwardedLaunch::current()) {
+            Ok(InstanceRole::Primary(listener)) => Some(listener),
+            Ok(InstanceRole::Forwarded) => return,
+            Err(error) => {
+                eprintln!("Failed to enforce a single instance: {error}");
+                None
+            }
+        }
+    };
+
     let builder = Builder::new()
         .commands(collect_commands![
             utils::file::exists,
             utils::core::app_ready,
             utils::window::get_mouse_and_window_position,
-            utils::window::get_window_kind,
-            utils::window::warm_window,
-            utils::window::cold_window,
-            utils::window::prewarm_window,
-            utils::window::discard_prewarm_window,
-            utils::window::record_renderer_bootstrap_ready,
-            utils::window::create_window,
+            utils::window::subscribe_mouse_and_window_position::<tauri::Wry>,
+            utils::window::unsubscribe_mouse_and_window_position::<tauri::Wry>,
+            utils::window::get_monitors::<tauri::Wry>,
+            utils::window::get_window_kind::<tauri::Wry>,
+            utils::window::get_window_state::<tauri::Wry>,
+            utils::window::warm_window::<tauri::Wry>,
+            utils::window::cold_window::<tauri::Wry>,
+            utils::window::prewarm_window::<tauri::Wry>,
+            utils::window::discard_prewarm_window::<tauri::Wry>,
+            utils::window::get_prewarm_pool_metrics,
+            utils::window::get_window_registry_snapshot::<tauri::Wry>,
+            utils::window::record_renderer_bootstrap_ready::<tauri::Wry>,
+            utils::window::create_window::<tauri::Wry>,
+            utils::window::take_window_launch_payload::<tauri::Wry>,
+            utils::window::set_window_session_payload::<tauri::Wry>,
+            utils::window::answer_shutdown_request::<tauri::Wry>,
+            utils::window_bus::send_window_message::<tauri::Wry>,
+            utils::window_bus::request_window_message::<tauri::Wry>,
+            utils::window_bus::reply_window_message::<tauri::Wry>,
             utils::sidecar::run_bun_hello_sidecar,
             greet,
             clean,
         ])
-        .events(collect_events![event::FullScreenEvent]);
+        .events(collect_events![
+            event::FullScreenEvent,
+            event::WindowStateEvent,
+            event::WindowLifecycleEvent,
+            utils::window_bus::WindowMessageEvent,
+        ]);
 
     #[cfg(debug_assertions)]
     builder
@@ -41,19 +82,41 @@ pub fn run() {
     tauri::Builder::default()
         .plugin(tauri_plugin_process::init())
         .plugin(tauri_plugin_updater::Builder::new().build())
+        .plugin(tauri_plugin_deep_link::init())
         .plugin(tauri_plugin_fs::init())
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_clipboard_manager::init())
         .plugin(tauri_plugin_os::init())
         .plugin(tauri_plugin_shell::init())
+        .manage(utils::window::WindowRegistry::default())
+        .manage(utils::shutdown::ShutdownHooks::default())
+        .manage(utils::window_state::WindowGeometryCache::default())
+        .manage(utils::session::SessionCache::default())
+        .manage(utils::window_bus::WindowMessages::default())
         .on_window_event(|window, event| {
             let label = window.label().to_string();
             let app = window.app_handle();
             match event {
                 tauri::WindowEvent::CloseRequested { api, .. } => {
-                    if utils::window::should_exit_on_window_close(&app, &label) {
+                    if let Some(window) = app.get_webview_window(&label) {
+                        utils::window_state::flush_window_state(&window);
+                    }
+                    if utils::window::handle_close_requested(window) {
                         api.prevent_close();
-                        utils::window::begin_graceful_shutdown(&app, &label);
+                    }
+                }
+                tauri::WindowEvent::Moved(_) => {
+                    utils::window_state::schedule_window_state_save(&app, &label);
+                }
+                tauri::WindowEvent::Resized(_) => {
+                    utils::window_state::schedule_window_state_save(&app, &label);
+                    if let Some(window) = app.get_webview_window(&label) {
+                        utils::window::sync_window_state(&window, None);
+                    }
+                }
+                tauri::WindowEvent::Focused(focused) => {
+                    if let Some(window) = app.get_webview_window(&label) {
+                        utils::window::sync_window_state(&window, Some(*focused));
                     }
                 }
                 tauri::WindowEvent::Destroyed => {
@@ -66,33 +129,86 @@ pub fn run() {
         .setup(move |app| {
             let handle = app.handle().clone();
             builder.mount_events(app);
+            let hook_handle = handle.clone();
+            app.state::<utils::shutdown::ShutdownHooks>().register(
+                "window-state",
+                0,
+                Duration::from_secs(2),
+                move || utils::window_state::persist_cached_window_states(hook_handle),
+            );
+            let hook_handle = handle.clone();
+            app.state::<utils::shutdown::ShutdownHooks>().register(
+                "session",
+                0,
+                Duration::from_secs(2),
+                move || utils::session::persist_frozen_session(hook_handle),
+            );
+            #[cfg(desktop)]
+            utils::tray::install_tray(&handle)?;
+            // Installed builds register the scheme through the bundle; dev builds
+            // on Linux and Windows have to do it themselves.
+            #[cfg(all(debug_assertions, any(target_os = "linux", windows)))]
+            {
+                use tauri_plugin_deep_link::DeepLinkExt;
+                app.deep_link().register_all()?;
+            }
             block_in_place(|| {
                 block_on(async move {
                     let local_data_dir = handle.path().app_local_data_dir()?;
                     std::fs::create_dir_all(&local_data_dir)?;
                     let db_path = local_data_dir.join("surreal.db");
-                    println!("DB initialized on {}", db_path.display());
                     let db_options = InitDbOptions::default()
                         .versioned(false)
                         .changefeed_gc_interval(None);
                     init_db_with_options(db_path, db_options).await?;
+                    utils::window_state::load_persisted_window_states(&handle).await?;
 
                     utils::window::configure_existing_primary_windows(&handle);
+                    utils::window::start_prepared_window_supervisor(&handle);
+                    if let Err(error) = utils::session::restore_session(&handle).await {
+                        eprintln!("Failed to restore session: {error}");
+                    }
+                    utils::session::start_session_checkpoints(&handle);
+                    let launch_args = std::env::args().skip(1).collect::<Vec<_>>();
+                    let launch_cwd = std::env::current_dir().ok();
+                    utils::launch_route::route_startup_launch(
+                        &handle,
+                        &launch_args,
+                        launch_cwd.as_deref(),
+                    );
+                    #[cfg(desktop)]
+                    if let Some(listener) = instance_listener {
+                        utils::single_instance::serve_forwarded_launches(&handle, listener);
+                    }
                     Ok(())
                 })
             })
         })
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(context)
+        .expect("error while building tauri application")
+        .run(|app, event| match event {
+            tauri::RunEvent::ExitRequested {
+                code: None, api, ..
+            } if utils::window::should_keep_running_without_windows(app)
+                || app.state::<utils::shutdown::ShutdownHooks>().is_running() =>
+            {
+                api.prevent_exit();
+            }
+            #[cfg(any(target_os = "macos", target_os = "ios"))]
+            tauri::RunEvent::Opened { urls } => {
+                utils::launch_route::route_opened_urls(app, &urls);
+            }
+            _ => {}
+        });
 }
 
 #[tauri::command]
 #[specta::specta]
-async fn greet(name: &str) -> Result<String, String> {
+async fn greet(name: &str) -> Result<String, AppError> {
     let _ = User::save_many(vec![User::from_id(name)])
         .await
-        .map_err(|e| e.to_string())?;
-    let users = User::list().await.map_err(|e| e.to_string())?;
+        .map_err(AppError::db_request_failed)?;
+    let users = User::list().await.map_err(AppError::db_request_failed)?;
 
     let ids = users
         .iter()
@@ -105,7 +221,9 @@ async fn greet(name: &str) -> Result<String, String> {
 
 #[tauri::command]
 #[specta::specta]
-async fn clean() -> Result<String, String> {
-    User::delete_all().await.map_err(|e| e.to_string())?;
+async fn clean() -> Result<String, AppError> {
+    User::delete_all()
+        .await
+        .map_err(AppError::db_request_failed)?;
     Ok("message cleaned".to_string())
 }
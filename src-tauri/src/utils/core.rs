@@ -1,12 +1,15 @@
 use super::event::WINDOW_READY;
-use super::window;
+use super::window::{self, WindowRegistry};
 use std::sync::atomic::Ordering;
-use tauri::WebviewWindow;
+use tauri::{Manager, WebviewWindow};
 
 #[tauri::command]
 #[specta::specta]
 pub async fn app_ready(window: WebviewWindow) {
-    if window::should_activate_window_on_app_ready(window.label()) {
+    if window
+        .state::<WindowRegistry>()
+        .should_activate_window_on_app_ready(window.label())
+    {
         window::activate_window(&window);
     }
     WINDOW_READY.store(true, Ordering::SeqCst);
@@ -0,0 +1,244 @@
+use serde::Serialize;
+use specta::Type;
+use std::fmt;
+
+/// Subsystem an error comes from, for handling that does not care about the
+/// exact code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Type)]
+pub enum AppErrorCategory {
+    Db,
+    Window,
+    Sidecar,
+    Io,
+    Validation,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Type)]
+pub struct AppErrorInfo {
+    pub category: AppErrorCategory,
+    /// Translation key of the message shown to the user.
+    pub message_key: String,
+    /// The underlying error, for logs and bug reports. It is never localized.
+    pub details: Option<String>,
+}
+
+/// Error returned by commands. It serializes as a union tagged by `code`, and
+/// codes are stable, so the frontend can branch on them.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Type)]
+#[serde(tag = "code")]
+pub enum AppError {
+    DbRequestFailed(AppErrorInfo),
+    CursorPositionUnavailable(AppErrorInfo),
+    WindowGeometryUnavailable(AppErrorInfo),
+    MonitorsUnavailable(AppErrorInfo),
+    WindowMessageUndelivered(AppErrorInfo),
+    WindowRequestTimedOut(AppErrorInfo),
+    WindowRequestDropped(AppErrorInfo),
+    SidecarEntryMissing(AppErrorInfo),
+    SidecarFailed(AppErrorInfo),
+    SidecarOutputInvalid(AppErrorInfo),
+    IoFailed(AppErrorInfo),
+    NoMessageRecipient(AppErrorInfo),
+    WindowDescriptorMissing(AppErrorInfo),
+    WindowBuildFailed(AppErrorInfo),
+}
+
+fn info(category: AppErrorCategory, message_key: &str, details: impl fmt::Display) -> AppErrorInfo {
+    AppErrorInfo {
+        category,
+        message_key: message_key.to_string(),
+        details: Some(details.to_string()),
+    }
+}
+
+impl AppError {
+    pub fn db_request_failed(error: impl fmt::Display) -> Self {
+        Self::DbRequestFailed(info(AppErrorCategory::Db, "error.db.request_failed", error))
+    }
+
+    pub fn cursor_position_unavailable(error: impl fmt::Display) -> Self {
+        Self::CursorPositionUnavailable(info(
+            AppErrorCategory::Window,
+            "error.window.cursor_position_unavailable",
+            error,
+        ))
+    }
+
+    pub fn window_geometry_unavailable(error: impl fmt::Display) -> Self {
+        Self::WindowGeometryUnavailable(info(
+            AppErrorCategory::Window,
+            "error.window.geometry_unavailable",
+            error,
+        ))
+    }
+
+    pub fn monitors_unavailable(error: impl fmt::Display) -> Self {
+        Self::MonitorsUnavailable(info(
+            AppErrorCategory::Window,
+            "error.window.monitors_unavailable",
+            error,
+        ))
+    }
+
+    pub fn window_message_undelivered(error: impl fmt::Display) -> Self {
+        Self::WindowMessageUndelivered(info(
+            AppErrorCategory::Window,
+            "error.window.message_undelivered",
+            error,
+        ))
+    }
+
+    pub fn window_request_timed_out(request_id: u32) -> Self {
+        Self::WindowRequestTimedOut(info(
+            AppErrorCategory::Window,
+            "error.window.request_timed_out",
+            format!("window request {request_id} timed out"),
+        ))
+    }
+
+    pub fn window_request_dropped(request_id: u32) -> Self {
+        Self::WindowRequestDropped(info(
+            AppErrorCategory::Window,
+            "error.window.request_dropped",
+            format!("window request {request_id} was dropped"),
+        ))
+    }
+
+    pub fn sidecar_entry_missing(error: impl fmt::Display) -> Self {
+        Self::SidecarEntryMissing(info(
+            AppErrorCategory::Sidecar,
+            "error.sidecar.entry_missing",
+            error,
+        ))
+    }
+
+    pub fn sidecar_failed(error: impl fmt::Display) -> Self {
+        Self::SidecarFailed(info(
+            AppErrorCategory::Sidecar,
+            "error.sidecar.failed",
+            error,
+        ))
+    }
+
+    pub fn sidecar_output_invalid(error: impl fmt::Display) -> Self {
+        Self::SidecarOutputInvalid(info(
+            AppErrorCategory::Sidecar,
+            "error.sidecar.output_invalid",
+            error,
+        ))
+    }
+
+    pub fn io_failed(error: impl fmt::Display) -> Self {
+        Self::IoFailed(info(AppErrorCategory::Io, "error.io.failed", error))
+    }
+
+    pub fn no_message_recipient(target: impl fmt::Debug) -> Self {
+        Self::NoMessageRecipient(info(
+            AppErrorCategory::Validation,
+            "error.validation.no_message_recipient",
+            format!("no window matches message target {target:?}"),
+        ))
+    }
+
+    pub fn window_descriptor_missing(window: impl fmt::Display) -> Self {
+        Self::WindowDescriptorMissing(info(
+            AppErrorCategory::Validation,
+            "error.validation.window_descriptor_missing",
+            format!("window {window} has no descriptor"),
+        ))
+    }
+
+    pub fn window_build_failed(error: impl fmt::Display) -> Self {
+        Self::WindowBuildFailed(info(
+            AppErrorCategory::Window,
+            "error.window.build_failed",
+            error,
+        ))
+    }
+
+    pub fn info(&self) -> &AppErrorInfo {
+        match self {
+            Self::DbRequestFailed(info)
+            | Self::CursorPositionUnavailable(info)
+            | Self::WindowGeometryUnavailable(info)
+            | Self::MonitorsUnavailable(info)
+            | Self::WindowMessageUndelivered(info)
+            | Self::WindowRequestTimedOut(info)
+            | Self::WindowRequestDropped(info)
+            | Self::SidecarEntryMissing(info)
+            | Self::SidecarFailed(info)
+            | Self::SidecarOutputInvalid(info)
+            | Self::IoFailed(info)
+            | Self::NoMessageRecipient(info)
+            | Self::WindowDescriptorMissing(info)
+            | Self::WindowBuildFailed(info) => info,
+        }
+    }
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let info = self.info();
+        match &info.details {
+            Some(details) => write!(f, "{}: {details}", info.message_key),
+            None => f.write_str(&info.message_key),
+        }
+    }
+}
+
+impl std::error::Error for AppError {}
+
+#[cfg(test)]
+mod tests {
+    use super::{AppError, AppErrorCategory};
+    use serde_json::json;
+
+    #[test]
+    fn errors_serialize_as_a_union_tagged_by_code() {
+        assert_eq!(
+            serde_json::to_value(AppError::db_request_failed("connection closed"))
+                .expect("failed to serialize error"),
+            json!({
+                "code": "DbRequestFailed",
+                "category": "Db",
+                "message_key": "error.db.request_failed",
+                "details": "connection closed",
+            })
+        );
+    }
+
+    #[test]
+    fn message_keys_are_namespaced_by_category() {
+        let errors = [
+            AppError::db_request_failed("locked"),
+            AppError::cursor_position_unavailable("no pointer"),
+            AppError::window_geometry_unavailable("minimized"),
+            AppError::monitors_unavailable("headless"),
+            AppError::window_message_undelivered("closed"),
+            AppError::window_request_timed_out(7),
+            AppError::window_request_dropped(7),
+            AppError::sidecar_entry_missing("hello.ts"),
+            AppError::sidecar_failed("exit 1"),
+            AppError::sidecar_output_invalid("invalid utf-8"),
+            AppError::io_failed("permission denied"),
+            AppError::no_message_recipient("main-9"),
+            AppError::window_descriptor_missing("Settings"),
+            AppError::window_build_failed("webview crashed"),
+        ];
+
+        for error in errors {
+            let info = error.info();
+            let category = match info.category {
+                AppErrorCategory::Db => "db",
+                AppErrorCategory::Window => "window",
+                AppErrorCategory::Sidecar => "sidecar",
+                AppErrorCategory::Io => "io",
+                AppErrorCategory::Validation => "validation",
+            };
+            assert!(
+                info.message_key.starts_with(&format!("error.{category}.")),
+                "{error} is filed under the wrong category"
+            );
+        }
+    }
+}
@@ -3,9 +3,67 @@ use specta::Type;
 use std::sync::atomic::AtomicBool;
 use tauri_specta::Event;
 
+use super::window::WindowName;
+
 #[derive(Serialize, Deserialize, Debug, Clone, Type, Event)]
 pub struct FullScreenEvent {
     pub is_fullscreen: bool,
 }
 
+/// Window flags as seen by the window they describe. Sent to that window only,
+/// and only when one of them changed.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Type, Event)]
+pub struct WindowStateEvent {
+    pub is_fullscreen: bool,
+    pub is_maximized: bool,
+    pub is_minimized: bool,
+    pub is_focused: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Type, Event)]
+#[serde(tag = "type")]
+pub enum WindowLifecycleEvent {
+    Created {
+        window: Option<WindowName>,
+        label: String,
+        visible: bool,
+    },
+    Prewarmed {
+        window: WindowName,
+        label: String,
+    },
+    Ready {
+        window: WindowName,
+        label: String,
+    },
+    Promoted {
+        window: Option<WindowName>,
+        label: String,
+    },
+    Destroyed {
+        window: Option<WindowName>,
+        label: String,
+    },
+    /// A window was hidden or shown again without being closed or created.
+    VisibilityChanged {
+        window: Option<WindowName>,
+        label: String,
+        visible: bool,
+    },
+    /// User windows in `awaiting_labels` answer with `answer_shutdown_request`.
+    GracefulShutdownRequested {
+        request_id: u32,
+        closing_label: Option<String>,
+        awaiting_labels: Vec<String>,
+    },
+    GracefulShutdownCancelled {
+        request_id: u32,
+        vetoed_by: String,
+    },
+    GracefulShutdownStarted {
+        closing_label: Option<String>,
+        pending_labels: Vec<String>,
+    },
+}
+
 pub static WINDOW_READY: AtomicBool = AtomicBool::new(false);
@@ -1,7 +1,11 @@
 use std::path::PathBuf;
 
+use super::error::AppError;
+
 #[tauri::command]
 #[specta::specta]
-pub fn exists(path: String) -> Result<bool, String> {
-    Ok(PathBuf::from(path).exists())
+pub fn exists(path: String) -> Result<bool, AppError> {
+    PathBuf::from(path)
+        .try_exists()
+        .map_err(AppError::io_failed)
 }
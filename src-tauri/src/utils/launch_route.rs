@@ -0,0 +1,364 @@
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Runtime, Url};
+
+use super::window::{self, WindowLaunchPayload, WindowName};
+
+/// Registered with the OS through `plugins.deep-link` in `tauri.conf.json`.
+const DEEP_LINK_SCHEME: &str = "app";
+const DEEP_LINK_OPEN_HOST: &str = "open";
+const ROUTE_PARAM: &str = "route";
+const WINDOW_FLAG: &str = "--window";
+const PAYLOAD_FLAG: &str = "--payload";
+
+/// A window an external launch asked for, resolved through the launch targets
+/// of the window descriptors.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LaunchRequest {
+    pub window: WindowName,
+    pub payload: Option<WindowLaunchPayload>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LaunchRouteError {
+    UnknownWindow(String),
+    InvalidDeepLink(String),
+    MissingFlagValue(&'static str),
+    PayloadWithoutWindow,
+    UnreadablePayload { path: PathBuf, reason: String },
+    InvalidPayload { path: PathBuf, reason: String },
+}
+
+impl fmt::Display for LaunchRouteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownWindow(target) => write!(f, "no window can be opened as {target}"),
+            Self::InvalidDeepLink(link) => {
+                write!(
+                    f,
+                    "deep link {link} is not of the form {DEEP_LINK_SCHEME}://{DEEP_LINK_OPEN_HOST}/<window>"
+                )
+            }
+            Self::MissingFlagValue(flag) => write!(f, "{flag} needs a value"),
+            Self::PayloadWithoutWindow => write!(f, "{PAYLOAD_FLAG} needs {WINDOW_FLAG}"),
+            Self::UnreadablePayload { path, reason } => {
+                write!(
+                    f,
+                    "failed to read launch payload {}: {reason}",
+                    path.display()
+                )
+            }
+            Self::InvalidPayload { path, reason } => {
+                write!(f, "launch payload {} is invalid: {reason}", path.display())
+            }
+        }
+    }
+}
+
+impl std::error::Error for LaunchRouteError {}
+
+/// Payload files may leave out either field, unlike the IPC payload.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct LaunchPayloadFile {
+    route: Option<String>,
+    params: HashMap<String, String>,
+}
+
+fn resolve_launch_target(target: &str) -> Result<WindowName, LaunchRouteError> {
+    window::window_kind_from_launch_target(target)
+        .ok_or_else(|| LaunchRouteError::UnknownWindow(target.to_string()))
+}
+
+fn is_deep_link(arg: &str) -> bool {
+    arg.strip_prefix(DEEP_LINK_SCHEME)
+        .is_some_and(|rest| rest.starts_with("://"))
+}
+
+/// `app://open/<window>?route=/notes&id=1` opens `<window>`; `route` becomes
+/// the payload route and every other query pair a payload param.
+fn parse_deep_link(link: &str) -> Result<LaunchRequest, LaunchRouteError> {
+    let invalid = || LaunchRouteError::InvalidDeepLink(link.to_string());
+    let url = Url::parse(link).map_err(|_| invalid())?;
+    if url.host_str() != Some(DEEP_LINK_OPEN_HOST) {
+        return Err(invalid());
+    }
+
+    let target = url.path().trim_matches('/');
+    if target.is_empty() || target.contains('/') {
+        return Err(invalid());
+    }
+
+    let window = resolve_launch_target(target)?;
+    let mut payload = WindowLaunchPayload::default();
+    for (key, value) in url.query_pairs() {
+        if key == ROUTE_PARAM {
+            payload.route = Some(value.into_owned());
+        } else {
+            payload.params.insert(key.into_owned(), value.into_owned());
+        }
+    }
+
+    Ok(LaunchRequest {
+        window,
+        payload: (payload != WindowLaunchPayload::default()).then_some(payload),
+    })
+}
+
+fn read_launch_payload_file(
+    path: &str,
+    cwd: Option<&Path>,
+) -> Result<WindowLaunchPayload, LaunchRouteError> {
+    let path = match cwd {
+        Some(cwd) => cwd.join(path),
+        None => PathBuf::from(path),
+    };
+    let contents =
+        std::fs::read_to_string(&path).map_err(|error| LaunchRouteError::UnreadablePayload {
+            path: path.clone(),
+            reason: error.to_string(),
+        })?;
+    let file: LaunchPayloadFile =
+        serde_json::from_str(&contents).map_err(|error| LaunchRouteError::InvalidPayload {
+            path,
+            reason: error.to_string(),
+        })?;
+
+    Ok(WindowLaunchPayload {
+        route: file.route,
+        params: file.params,
+    })
+}
+
+/// Both `--window support` and `--window=support` are accepted. Arguments the
+/// router does not know are left to the rest of the app.
+fn parse_launch_flags(
+    args: &[String],
+    cwd: Option<&Path>,
+) -> Result<Option<LaunchRequest>, LaunchRouteError> {
+    let mut target = None;
+    let mut payload_path = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let (flag, inline_value) = match arg.split_once('=') {
+            Some((flag, value)) => (flag, Some(value.to_string())),
+            None => (arg.as_str(), None),
+        };
+        let (flag, slot) = match flag {
+            WINDOW_FLAG => (WINDOW_FLAG, &mut target),
+            PAYLOAD_FLAG => (PAYLOAD_FLAG, &mut payload_path),
+            _ => continue,
+        };
+
+        let value = inline_value
+            .or_else(|| args.next().cloned())
+            .filter(|value| !value.is_empty() && !value.starts_with("--"))
+            .ok_or(LaunchRouteError::MissingFlagValue(flag))?;
+        *slot = Some(value);
+    }
+
+    let Some(target) = target else {
+        return match payload_path {
+            Some(_) => Err(LaunchRouteError::PayloadWithoutWindow),
+            None => Ok(None),
+        };
+    };
+
+    let window = resolve_launch_target(&target)?;
+    let payload = payload_path
+        .map(|path| read_launch_payload_file(&path, cwd))
+        .transpose()?;
+    Ok(Some(LaunchRequest { window, payload }))
+}
+
+/// Returns `None` when the arguments do not ask for a window. A deep link
+/// takes precedence over flags, since the OS passes it as the only argument.
+pub fn parse_launch_request(
+    args: &[String],
+    cwd: Option<&Path>,
+) -> Result<Option<LaunchRequest>, LaunchRouteError> {
+    match args.iter().find(|arg| is_deep_link(arg)) {
+        Some(link) => parse_deep_link(link).map(Some),
+        None => parse_launch_flags(args, cwd),
+    }
+}
+
+/// Singleton kinds that are already open are focused and receive the payload
+/// over the window bus; everything else opens a new window.
+pub fn open_launch_request<R: Runtime>(app: &AppHandle<R>, request: LaunchRequest) {
+    let _ = window::open_window(app, None, request.window, None, request.payload);
+}
+
+/// The window created from the app config is still waiting for its renderer
+/// at startup, so a launch aimed at its kind is handed to it instead of
+/// opening a second window.
+pub fn route_startup_launch<R: Runtime>(app: &AppHandle<R>, args: &[String], cwd: Option<&Path>) {
+    let request = match parse_launch_request(args, cwd) {
+        Ok(Some(request)) => request,
+        Ok(None) => return,
+        Err(error) => {
+            eprintln!("Ignoring launch arguments: {error}");
+            return;
+        }
+    };
+
+    if let Some(startup_window) = window::primary_window(app, request.window) {
+        window::set_window_launch_payload(app, startup_window.label(), request.payload);
+        return;
+    }
+
+    open_launch_request(app, request);
+}
+
+/// Deep links delivered to the running app by the OS rather than as process
+/// arguments, as macOS does.
+#[cfg(any(target_os = "macos", target_os = "ios"))]
+pub fn route_opened_urls<R: Runtime>(app: &AppHandle<R>, urls: &[Url]) {
+    for url in urls {
+        match parse_deep_link(url.as_str()) {
+            Ok(request) => open_launch_request(app, request),
+            Err(error) => eprintln!("Ignoring opened url: {error}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LaunchRequest, LaunchRouteError, parse_launch_request};
+    use crate::utils::window::{WindowLaunchPayload, WindowName};
+    use std::collections::HashMap;
+
+    fn args(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    #[test]
+    fn deep_links_open_the_named_window_with_query_payload() {
+        assert_eq!(
+            parse_launch_request(&args(&["app://open/support?route=/tickets&id=42"]), None),
+            Ok(Some(LaunchRequest {
+                window: WindowName::Support,
+                payload: Some(WindowLaunchPayload {
+                    route: Some("/tickets".to_string()),
+                    params: HashMap::from([("id".to_string(), "42".to_string())]),
+                }),
+            }))
+        );
+        assert_eq!(
+            parse_launch_request(&args(&["app://open/main/"]), None),
+            Ok(Some(LaunchRequest {
+                window: WindowName::Main,
+                payload: None,
+            }))
+        );
+    }
+
+    #[test]
+    fn malformed_deep_links_and_unknown_targets_are_rejected() {
+        assert_eq!(
+            parse_launch_request(&args(&["app://settings/main"]), None),
+            Err(LaunchRouteError::InvalidDeepLink(
+                "app://settings/main".to_string()
+            ))
+        );
+        assert_eq!(
+            parse_launch_request(&args(&["app://open/"]), None),
+            Err(LaunchRouteError::InvalidDeepLink("app://open/".to_string()))
+        );
+        assert_eq!(
+            parse_launch_request(&args(&["app://open/settings"]), None),
+            Err(LaunchRouteError::UnknownWindow("settings".to_string()))
+        );
+    }
+
+    #[test]
+    fn window_flag_accepts_separate_and_inline_values() {
+        let expected = Ok(Some(LaunchRequest {
+            window: WindowName::Support,
+            payload: None,
+        }));
+
+        assert_eq!(
+            parse_launch_request(&args(&["--verbose", "--window", "support"]), None),
+            expected
+        );
+        assert_eq!(
+            parse_launch_request(&args(&["--window=support"]), None),
+            expected
+        );
+    }
+
+    #[test]
+    fn arguments_without_a_window_are_not_routed() {
+        assert_eq!(parse_launch_request(&args(&[]), None), Ok(None));
+        assert_eq!(
+            parse_launch_request(&args(&["--open", "notes.md"]), None),
+            Ok(None)
+        );
+    }
+
+    #[test]
+    fn incomplete_flags_are_rejected() {
+        assert_eq!(
+            parse_launch_request(&args(&["--window"]), None),
+            Err(LaunchRouteError::MissingFlagValue("--window"))
+        );
+        assert_eq!(
+            parse_launch_request(&args(&["--window", "--payload", "a.json"]), None),
+            Err(LaunchRouteError::MissingFlagValue("--window"))
+        );
+        assert_eq!(
+            parse_launch_request(&args(&["--payload", "a.json"]), None),
+            Err(LaunchRouteError::PayloadWithoutWindow)
+        );
+        assert_eq!(
+            parse_launch_request(&args(&["--window", "settings"]), None),
+            Err(LaunchRouteError::UnknownWindow("settings".to_string()))
+        );
+    }
+
+    #[test]
+    fn payload_files_are_read_relative_to_the_launch_directory() {
+        let cwd = std::env::temp_dir().join(format!("launch-route-test-{}", std::process::id()));
+        std::fs::create_dir_all(&cwd).expect("failed to create test directory");
+        std::fs::write(cwd.join("payload.json"), r#"{ "route": "/tickets/7" }"#)
+            .expect("failed to write payload");
+        std::fs::write(cwd.join("broken.json"), "{").expect("failed to write payload");
+
+        let routed = parse_launch_request(
+            &args(&["--window", "support", "--payload", "payload.json"]),
+            Some(&cwd),
+        );
+        let broken = parse_launch_request(
+            &args(&["--window", "support", "--payload", "broken.json"]),
+            Some(&cwd),
+        );
+        let missing = parse_launch_request(
+            &args(&["--window", "support", "--payload", "missing.json"]),
+            Some(&cwd),
+        );
+        let _ = std::fs::remove_dir_all(&cwd);
+
+        assert_eq!(
+            routed,
+            Ok(Some(LaunchRequest {
+                window: WindowName::Support,
+                payload: Some(WindowLaunchPayload {
+                    route: Some("/tickets/7".to_string()),
+                    params: HashMap::new(),
+                }),
+            }))
+        );
+        assert!(matches!(
+            broken,
+            Err(LaunchRouteError::InvalidPayload { path, .. }) if path == cwd.join("broken.json")
+        ));
+        assert!(matches!(
+            missing,
+            Err(LaunchRouteError::UnreadablePayload { path, .. })
+                if path == cwd.join("missing.json")
+        ));
+    }
+}
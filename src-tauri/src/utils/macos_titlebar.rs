@@ -12,7 +12,7 @@ use objc2_app_kit::{
 use objc2_foundation::{
     MainThreadMarker, NSNotification, NSNotificationCenter, NSObjectProtocol, NSOperationQueue,
 };
-use tauri::WebviewWindow; // Manager for emit
+use tauri::{Runtime, WebviewWindow}; // Manager for emit
 use tauri_specta::Event;
 
 use crate::utils::event::FullScreenEvent;
@@ -45,7 +45,7 @@ unsafe fn hide_native_traffic_lights_initial(
     set_native_traffic_lights_hidden(ns_window, true, mtm);
 }
 
-pub fn setup_custom_macos_titlebar(window: &WebviewWindow) {
+pub fn setup_custom_macos_titlebar<R: Runtime>(window: &WebviewWindow<R>) {
     window
         .set_title_bar_style(tauri::TitleBarStyle::Overlay)
         .expect("Failed to set title bar style to Overlay");
@@ -87,7 +87,10 @@ pub struct FullscreenStateManager {
 }
 
 impl FullscreenStateManager {
-    pub fn new(webview_window: &WebviewWindow, mtm: MainThreadMarker) -> Option<Self> {
+    pub fn new<R: Runtime>(
+        webview_window: &WebviewWindow<R>,
+        mtm: MainThreadMarker,
+    ) -> Option<Self> {
         let ns_window_ptr = webview_window.ns_window().ok()? as *mut objc2_app_kit::NSWindow;
         if ns_window_ptr.is_null() {
             return None;
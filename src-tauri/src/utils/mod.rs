@@ -1,6 +1,17 @@
 pub mod core;
+pub mod error;
 pub mod event;
 pub mod file;
+pub mod launch_route;
 pub mod macos_titlebar;
+pub mod session;
+pub mod shutdown;
 pub mod sidecar;
-pub mod window;
+#[cfg(desktop)]
+pub mod single_instance;
+#[cfg(desktop)]
+pub mod tray;
+pub mod window;
+pub mod window_bus;
+pub mod window_kinds;
+pub mod window_state;
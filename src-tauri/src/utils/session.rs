@@ -0,0 +1,266 @@
+use crate::domain::models::session::{Session, SessionWindow};
+use std::collections::HashSet;
+use std::sync::{Mutex, MutexGuard};
+use std::time::Duration;
+use tauri::{AppHandle, Manager, Runtime};
+use tauri::{PhysicalPosition, PhysicalSize};
+
+use super::window::{
+    self, WindowLaunchPayload, WindowName, WindowRegistry, clamp_i64_to_i32, clamp_i64_to_u32,
+};
+use super::window_state::{self, WindowGeometry};
+
+const SESSION_RECORD_ID: &str = "last";
+const SESSION_CHECKPOINT_INTERVAL: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Default)]
+struct SessionCacheState {
+    /// Windows as last written, so unchanged checkpoints are skipped.
+    persisted: Option<Vec<SessionWindow>>,
+    /// Windows captured when graceful shutdown started closing them. Once set,
+    /// checkpoints stop, since the windows are about to disappear.
+    frozen: Option<Vec<SessionWindow>>,
+}
+
+/// What the session subsystem knows about the stored session. It is managed
+/// as Tauri state.
+#[derive(Debug, Default)]
+pub struct SessionCache {
+    state: Mutex<SessionCacheState>,
+}
+
+impl SessionCache {
+    fn lock(&self) -> MutexGuard<'_, SessionCacheState> {
+        self.state.lock().expect("session cache poisoned")
+    }
+}
+
+/// Sessions are opt-in per window kind through `restore_session`.
+fn is_session_restore_enabled() -> bool {
+    WindowName::ALL.into_iter().any(window::restores_session)
+}
+
+fn session_window(
+    label: &str,
+    payload: Option<WindowLaunchPayload>,
+    geometry: &WindowGeometry,
+) -> SessionWindow {
+    let payload = payload.unwrap_or_default();
+    SessionWindow {
+        label: label.to_string(),
+        route: payload.route,
+        params: payload.params,
+        x: i64::from(geometry.position.x),
+        y: i64::from(geometry.position.y),
+        width: i64::from(geometry.size.width),
+        height: i64::from(geometry.size.height),
+        maximized: geometry.maximized,
+        fullscreen: geometry.fullscreen,
+        monitor: geometry.monitor.clone(),
+    }
+}
+
+fn session_window_payload(window: &SessionWindow) -> Option<WindowLaunchPayload> {
+    let payload = WindowLaunchPayload {
+        route: window.route.clone(),
+        params: window.params.clone(),
+    };
+    (payload != WindowLaunchPayload::default()).then_some(payload)
+}
+
+fn session_window_geometry(window: &SessionWindow) -> WindowGeometry {
+    WindowGeometry {
+        position: PhysicalPosition::new(clamp_i64_to_i32(window.x), clamp_i64_to_i32(window.y)),
+        size: PhysicalSize::new(
+            clamp_i64_to_u32(window.width),
+            clamp_i64_to_u32(window.height),
+        ),
+        maximized: window.maximized,
+        fullscreen: window.fullscreen,
+        monitor: window.monitor.clone(),
+    }
+}
+
+/// Visible user windows of the kinds that restore their session, in label
+/// order, which is the order they are reopened in.
+pub fn capture_session<R: Runtime>(app: &AppHandle<R>) -> Vec<SessionWindow> {
+    capture_session_windows(app, window::restores_session)
+}
+
+/// Same as [`capture_session`], for the kinds `restores` accepts.
+pub fn capture_session_windows<R: Runtime>(
+    app: &AppHandle<R>,
+    restores: impl Fn(WindowName) -> bool,
+) -> Vec<SessionWindow> {
+    let registry = app.state::<WindowRegistry>();
+    window::user_windows(app)
+        .into_iter()
+        .filter(|user_window| {
+            user_window.is_visible().unwrap_or(false)
+                && window::window_kind_from_label(user_window.label()).is_some_and(&restores)
+        })
+        .filter_map(|user_window| {
+            let geometry = window_state::current_window_geometry(&user_window)?;
+            let payload = registry.session_payload_for_label(user_window.label());
+            Some(session_window(user_window.label(), payload, &geometry))
+        })
+        .collect()
+}
+
+async fn save_session<R: Runtime>(
+    app: &AppHandle<R>,
+    windows: Vec<SessionWindow>,
+) -> anyhow::Result<()> {
+    Session::save_many(vec![Session {
+        id: SESSION_RECORD_ID.into(),
+        windows: windows.clone(),
+    }])
+    .await?;
+    app.state::<SessionCache>().lock().persisted = Some(windows);
+    Ok(())
+}
+
+/// Captures the session before graceful shutdown closes the windows; the
+/// `session` shutdown hook writes it once they are gone.
+pub fn freeze_session<R: Runtime>(app: &AppHandle<R>) {
+    if !is_session_restore_enabled() {
+        return;
+    }
+
+    let windows = capture_session(app);
+    app.state::<SessionCache>().lock().frozen = Some(windows);
+}
+
+pub async fn persist_frozen_session<R: Runtime>(app: AppHandle<R>) -> anyhow::Result<()> {
+    let Some(windows) = app.state::<SessionCache>().lock().frozen.clone() else {
+        return Ok(());
+    };
+
+    save_session(&app, windows).await
+}
+
+/// Returns false once shutdown has frozen the session.
+async fn checkpoint_session<R: Runtime>(app: &AppHandle<R>) -> anyhow::Result<bool> {
+    let windows = capture_session(app);
+    {
+        let session_cache = app.state::<SessionCache>();
+        let cache = session_cache.lock();
+        if cache.frozen.is_some() {
+            return Ok(false);
+        }
+        if cache.persisted.as_ref() == Some(&windows) {
+            return Ok(true);
+        }
+    }
+
+    save_session(app, windows).await?;
+    Ok(true)
+}
+
+/// Writes the session every `SESSION_CHECKPOINT_INTERVAL` while it changes,
+/// so a crash loses at most one interval of window changes.
+pub fn start_session_checkpoints<R: Runtime>(app: &AppHandle<R>) {
+    if !is_session_restore_enabled() {
+        return;
+    }
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut ticker = tokio::time::interval(SESSION_CHECKPOINT_INTERVAL);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+        // The first tick completes immediately, before anything has changed.
+        ticker.tick().await;
+        loop {
+            ticker.tick().await;
+            match checkpoint_session(&app).await {
+                Ok(true) => {}
+                Ok(false) => return,
+                Err(error) => eprintln!("Failed to checkpoint session: {error}"),
+            }
+        }
+    });
+}
+
+/// Reopens the windows of the last session through `open_window`. The first
+/// window of a kind takes over that kind's primary window when the app
+/// config has already created it.
+pub async fn restore_session<R: Runtime>(app: &AppHandle<R>) -> anyhow::Result<()> {
+    if !is_session_restore_enabled() {
+        return Ok(());
+    }
+
+    let Some(session) = Session::list().await?.into_iter().next() else {
+        return Ok(());
+    };
+
+    let mut reused_primary_windows = HashSet::new();
+    for saved in &session.windows {
+        let Some(name) = window::window_kind_from_label(&saved.label)
+            .filter(|name| window::restores_session(*name))
+        else {
+            continue;
+        };
+
+        let payload = session_window_payload(saved);
+        let restored = match window::primary_window(app, name) {
+            Some(primary) if reused_primary_windows.insert(name) => {
+                window::set_window_launch_payload(app, primary.label(), payload);
+                primary
+            }
+            _ => {
+                let Ok(opened) = window::open_window(app, None, name, None, payload) else {
+                    continue;
+                };
+                let Some(opened_window) = app.get_webview_window(&opened.kind.label) else {
+                    continue;
+                };
+                opened_window
+            }
+        };
+        let _ =
+            window_state::apply_window_geometry(app, &restored, &session_window_geometry(saved));
+    }
+
+    app.state::<SessionCache>().lock().persisted = Some(session.windows);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{session_window, session_window_geometry, session_window_payload};
+    use crate::utils::window::WindowLaunchPayload;
+    use crate::utils::window_state::WindowGeometry;
+    use std::collections::HashMap;
+    use tauri::{PhysicalPosition, PhysicalSize};
+
+    fn geometry() -> WindowGeometry {
+        WindowGeometry {
+            position: PhysicalPosition::new(-1200, 80),
+            size: PhysicalSize::new(1024, 700),
+            maximized: true,
+            fullscreen: false,
+            monitor: Some("DP-2".to_string()),
+        }
+    }
+
+    #[test]
+    fn session_windows_reopen_with_their_payload_and_geometry() {
+        let payload = WindowLaunchPayload {
+            route: Some("/notes".to_string()),
+            params: HashMap::from([("id".to_string(), "7".to_string())]),
+        };
+
+        let saved = session_window("main-1", Some(payload.clone()), &geometry());
+
+        assert_eq!(session_window_payload(&saved), Some(payload));
+        assert_eq!(session_window_geometry(&saved), geometry());
+    }
+
+    #[test]
+    fn windows_without_a_payload_reopen_without_one() {
+        let saved = session_window("main", None, &geometry());
+
+        assert_eq!(saved.route, None);
+        assert_eq!(session_window_payload(&saved), None);
+    }
+}
@@ -0,0 +1,222 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager, Runtime};
+
+type ShutdownHookFuture = Pin<Box<dyn Future<Output = anyhow::Result<()>> + Send>>;
+
+struct ShutdownHook {
+    name: String,
+    priority: i32,
+    timeout: Duration,
+    run: Box<dyn FnOnce() -> ShutdownHookFuture + Send>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum ShutdownHookOutcome {
+    Completed,
+    Failed(String),
+    TimedOut,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ShutdownHookReport {
+    name: String,
+    outcome: ShutdownHookOutcome,
+    elapsed: Duration,
+}
+
+/// Async work that must finish after the last window is destroyed and before
+/// the process exits, such as persisting state. It is managed as Tauri state
+/// and every hook runs at most once.
+#[derive(Default)]
+pub struct ShutdownHooks {
+    hooks: Mutex<Vec<ShutdownHook>>,
+    running: AtomicBool,
+}
+
+impl ShutdownHooks {
+    fn lock(&self) -> MutexGuard<'_, Vec<ShutdownHook>> {
+        self.hooks.lock().expect("shutdown hooks poisoned")
+    }
+
+    /// Hooks run one at a time in ascending `priority`, ties in registration
+    /// order, so a hook can rely on every earlier one having finished. A hook
+    /// that outlives its `timeout` is abandoned and shutdown moves on.
+    pub fn register<F, Fut>(
+        &self,
+        name: impl Into<String>,
+        priority: i32,
+        timeout: Duration,
+        hook: F,
+    ) where
+        F: FnOnce() -> Fut + Send + 'static,
+        Fut: Future<Output = anyhow::Result<()>> + Send + 'static,
+    {
+        self.lock().push(ShutdownHook {
+            name: name.into(),
+            priority,
+            timeout,
+            run: Box::new(move || Box::pin(hook())),
+        });
+    }
+
+    fn take_ordered(&self) -> Vec<ShutdownHook> {
+        let mut hooks = std::mem::take(&mut *self.lock());
+        hooks.sort_by_key(|hook| hook.priority);
+        hooks
+    }
+
+    /// True once the hooks have started; Tauri's own exit request, sent when
+    /// the last window is gone, must be held back until they are done.
+    pub fn is_running(&self) -> bool {
+        self.running.load(Ordering::SeqCst)
+    }
+}
+
+/// Each hook runs in its own task, so a panicking hook is reported as a
+/// failure instead of taking the exit down with it.
+async fn run_shutdown_hooks(hooks: Vec<ShutdownHook>) -> Vec<ShutdownHookReport> {
+    let mut reports = Vec::with_capacity(hooks.len());
+    for hook in hooks {
+        let started = Instant::now();
+        let task = tauri::async_runtime::spawn((hook.run)());
+        let outcome = match tokio::time::timeout(hook.timeout, task).await {
+            Ok(Ok(Ok(()))) => ShutdownHookOutcome::Completed,
+            Ok(Ok(Err(error))) => ShutdownHookOutcome::Failed(error.to_string()),
+            Ok(Err(error)) => ShutdownHookOutcome::Failed(error.to_string()),
+            Err(_) => ShutdownHookOutcome::TimedOut,
+        };
+        reports.push(ShutdownHookReport {
+            name: hook.name,
+            outcome,
+            elapsed: started.elapsed(),
+        });
+    }
+    reports
+}
+
+fn log_shutdown_hook_report(report: &ShutdownHookReport) {
+    let name = &report.name;
+    let elapsed_ms = report.elapsed.as_millis();
+    match &report.outcome {
+        ShutdownHookOutcome::Completed => {
+            println!("Shutdown hook {name} finished in {elapsed_ms}ms");
+        }
+        ShutdownHookOutcome::Failed(error) => {
+            eprintln!("Shutdown hook {name} failed after {elapsed_ms}ms: {error}");
+        }
+        ShutdownHookOutcome::TimedOut => {
+            eprintln!("Shutdown hook {name} timed out after {elapsed_ms}ms");
+        }
+    }
+}
+
+/// Runs the registered hooks, then exits. Later calls while the hooks are
+/// running are ignored, so every exit path of a graceful shutdown can call it.
+pub fn exit_after_shutdown_hooks<R: Runtime>(app: &AppHandle<R>) {
+    let shutdown_hooks = app.state::<ShutdownHooks>();
+    if shutdown_hooks.running.swap(true, Ordering::SeqCst) {
+        return;
+    }
+
+    let hooks = shutdown_hooks.take_ordered();
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        for report in run_shutdown_hooks(hooks).await {
+            log_shutdown_hook_report(&report);
+        }
+        app.exit(0);
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ShutdownHookOutcome, ShutdownHooks, run_shutdown_hooks};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    const HOOK_TIMEOUT: Duration = Duration::from_secs(5);
+
+    fn record_run(runs: &Arc<Mutex<Vec<&'static str>>>, name: &'static str) {
+        runs.lock().expect("runs poisoned").push(name);
+    }
+
+    fn panicking_hook() -> anyhow::Result<()> {
+        panic!("hook panicked")
+    }
+
+    #[test]
+    fn hooks_run_once_in_priority_then_registration_order() {
+        let hooks = ShutdownHooks::default();
+        let runs = Arc::new(Mutex::new(Vec::new()));
+        for (name, priority) in [("database", 10), ("window-state", 0), ("sidecars", 0)] {
+            let runs = runs.clone();
+            hooks.register(name, priority, HOOK_TIMEOUT, move || async move {
+                record_run(&runs, name);
+                Ok(())
+            });
+        }
+
+        let reports = tauri::async_runtime::block_on(run_shutdown_hooks(hooks.take_ordered()));
+
+        assert_eq!(
+            *runs.lock().expect("runs poisoned"),
+            vec!["window-state", "sidecars", "database"]
+        );
+        assert!(
+            reports
+                .iter()
+                .all(|report| report.outcome == ShutdownHookOutcome::Completed)
+        );
+        assert!(hooks.take_ordered().is_empty());
+    }
+
+    #[test]
+    fn failing_slow_and_panicking_hooks_do_not_stop_later_ones() {
+        let hooks = ShutdownHooks::default();
+        let runs = Arc::new(Mutex::new(Vec::new()));
+        hooks.register("failing", 0, HOOK_TIMEOUT, || async {
+            Err(anyhow::anyhow!("database is locked"))
+        });
+        hooks.register("slow", 1, Duration::from_millis(20), || async {
+            tokio::time::sleep(Duration::from_secs(60)).await;
+            Ok(())
+        });
+        hooks.register("panicking", 2, HOOK_TIMEOUT, || async { panicking_hook() });
+        let last_runs = runs.clone();
+        hooks.register("last", 3, HOOK_TIMEOUT, move || async move {
+            record_run(&last_runs, "last");
+            Ok(())
+        });
+
+        let reports = tauri::async_runtime::block_on(run_shutdown_hooks(hooks.take_ordered()));
+        let outcomes = reports
+            .into_iter()
+            .map(|report| (report.name, report.outcome))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            outcomes[0],
+            (
+                "failing".to_string(),
+                ShutdownHookOutcome::Failed("database is locked".to_string())
+            )
+        );
+        assert_eq!(
+            outcomes[1],
+            ("slow".to_string(), ShutdownHookOutcome::TimedOut)
+        );
+        assert!(matches!(
+            &outcomes[2],
+            (name, ShutdownHookOutcome::Failed(_)) if name == "panicking"
+        ));
+        assert_eq!(
+            outcomes[3],
+            ("last".to_string(), ShutdownHookOutcome::Completed)
+        );
+        assert_eq!(*runs.lock().expect("runs poisoned"), vec!["last"]);
+    }
+}
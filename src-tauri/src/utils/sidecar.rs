@@ -6,6 +6,8 @@ use tauri::Manager;
 use tauri::path::BaseDirectory;
 use tauri_plugin_shell::ShellExt;
 
+use super::error::AppError;
+
 #[derive(Debug, Serialize, specta::Type)]
 pub struct BunSidecarOutput {
     pub ok: bool,
@@ -14,7 +16,7 @@ pub struct BunSidecarOutput {
     pub stderr: String,
 }
 
-fn resolve_hello_entry(app: &AppHandle) -> std::result::Result<PathBuf, String> {
+fn resolve_hello_entry(app: &AppHandle) -> std::result::Result<PathBuf, AppError> {
     #[cfg(debug_assertions)]
     {
         let dev_entry = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../sidecar-bun/hello.ts");
@@ -25,7 +27,7 @@ fn resolve_hello_entry(app: &AppHandle) -> std::result::Result<PathBuf, String>
 
     app.path()
         .resolve("sidecar-bun/hello.ts", BaseDirectory::Resource)
-        .map_err(|err: tauri::Error| err.to_string())
+        .map_err(AppError::sidecar_entry_missing)
 }
 
 #[tauri::command]
@@ -33,22 +35,22 @@ fn resolve_hello_entry(app: &AppHandle) -> std::result::Result<PathBuf, String>
 pub async fn run_bun_hello_sidecar(
     app: AppHandle,
     input: Option<String>,
-) -> std::result::Result<BunSidecarOutput, String> {
+) -> std::result::Result<BunSidecarOutput, AppError> {
     let entry = resolve_hello_entry(&app)?;
     let value = input.unwrap_or_else(|| "hello".to_string());
 
     let output = app
         .shell()
         .sidecar("bun-runtime")
-        .map_err(|err| err.to_string())?
+        .map_err(AppError::sidecar_failed)?
         .arg(entry.to_string_lossy().to_string())
         .arg(value)
         .output()
         .await
-        .map_err(|err| err.to_string())?;
+        .map_err(AppError::sidecar_failed)?;
 
-    let stdout = String::from_utf8(output.stdout).map_err(|err| err.to_string())?;
-    let stderr = String::from_utf8(output.stderr).map_err(|err| err.to_string())?;
+    let stdout = String::from_utf8(output.stdout).map_err(AppError::sidecar_output_invalid)?;
+    let stderr = String::from_utf8(output.stderr).map_err(AppError::sidecar_output_invalid)?;
 
     Ok(BunSidecarOutput {
         ok: output.status.success(),
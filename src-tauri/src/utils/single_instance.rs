@@ -0,0 +1,230 @@
+use interprocess::local_socket::{
+    GenericFilePath, GenericNamespaced, Listener, ListenerOptions, Name, Stream, prelude::*,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::Path;
+use tauri::{AppHandle, Runtime};
+
+use super::launch_route;
+use super::window::{self, WindowLaunchPayload, WindowName};
+
+const LAUNCH_ACK: &str = "ok";
+
+/// What a second launch hands over to the running instance before it exits.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ForwardedLaunch {
+    pub args: Vec<String>,
+    pub cwd: Option<String>,
+}
+
+impl ForwardedLaunch {
+    pub fn current() -> Self {
+        Self {
+            args: std::env::args().skip(1).collect(),
+            cwd: std::env::current_dir()
+                .ok()
+                .map(|cwd| cwd.to_string_lossy().into_owned()),
+        }
+    }
+
+    fn window_launch_payload(&self) -> WindowLaunchPayload {
+        let mut params = HashMap::new();
+        if let Some(cwd) = &self.cwd {
+            params.insert("cwd".to_string(), cwd.clone());
+        }
+        if !self.args.is_empty() {
+            params.insert(
+                "args".to_string(),
+                serde_json::to_string(&self.args).unwrap_or_default(),
+            );
+        }
+
+        WindowLaunchPayload {
+            route: None,
+            params,
+        }
+    }
+}
+
+pub enum InstanceRole {
+    /// This process owns the socket and must serve forwarded launches.
+    Primary(Listener),
+    /// The launch was handed to the running instance; this process should exit.
+    Forwarded,
+}
+
+/// Socket names are per user, so two accounts on one machine never share an
+/// instance.
+fn instance_socket_file_name(identifier: &str) -> String {
+    let user = std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .unwrap_or_default();
+    format!("{identifier}-{user}.instance.sock")
+}
+
+/// Namespaced sockets are preferred because they leave no stale file behind
+/// after a crash.
+fn instance_socket_name(identifier: &str) -> io::Result<Name<'static>> {
+    let name = instance_socket_file_name(identifier);
+    if GenericNamespaced::is_supported() {
+        name.to_ns_name::<GenericNamespaced>()
+    } else {
+        std::env::temp_dir()
+            .join(name)
+            .to_fs_name::<GenericFilePath>()
+    }
+}
+
+fn forward_launch(name: Name<'_>, launch: &ForwardedLaunch) -> io::Result<()> {
+    let mut stream = BufReader::new(Stream::connect(name)?);
+    let mut message = serde_json::to_string(launch)?;
+    message.push('\n');
+    stream.get_mut().write_all(message.as_bytes())?;
+
+    let mut ack = String::new();
+    stream.read_line(&mut ack)?;
+    if ack.trim_end() != LAUNCH_ACK {
+        return Err(io::Error::other(
+            "running instance did not accept the launch",
+        ));
+    }
+    Ok(())
+}
+
+/// Becomes the primary instance, or forwards `launch` to the one already
+/// running. A socket file left by a crashed instance is removed and claimed.
+pub fn claim_single_instance(
+    identifier: &str,
+    launch: &ForwardedLaunch,
+) -> io::Result<InstanceRole> {
+    match ListenerOptions::new()
+        .name(instance_socket_name(identifier)?)
+        .create_sync()
+    {
+        Ok(listener) => return Ok(InstanceRole::Primary(listener)),
+        Err(error) if error.kind() != io::ErrorKind::AddrInUse => return Err(error),
+        Err(_) => {}
+    }
+
+    match forward_launch(instance_socket_name(identifier)?, launch) {
+        Ok(()) => Ok(InstanceRole::Forwarded),
+        Err(error) if GenericNamespaced::is_supported() => Err(error),
+        Err(_) => {
+            let _ = std::fs::remove_file(
+                std::env::temp_dir().join(instance_socket_file_name(identifier)),
+            );
+            ListenerOptions::new()
+                .name(instance_socket_name(identifier)?)
+                .create_sync()
+                .map(InstanceRole::Primary)
+        }
+    }
+}
+
+fn read_forwarded_launch(stream: Stream) -> io::Result<ForwardedLaunch> {
+    let mut stream = BufReader::new(stream);
+    let mut message = String::new();
+    stream.read_line(&mut message)?;
+    let launch = serde_json::from_str(&message)?;
+
+    stream
+        .get_mut()
+        .write_all(format!("{LAUNCH_ACK}\n").as_bytes())?;
+    Ok(launch)
+}
+
+/// A launch that names a window through a deep link or `--window` is routed
+/// there. Any other launch opens a main window through the same path as
+/// `create_window`, so a ready prepared window is promoted when available.
+fn open_forwarded_launch<R: Runtime>(app: &AppHandle<R>, launch: ForwardedLaunch) {
+    match launch_route::parse_launch_request(&launch.args, launch.cwd.as_deref().map(Path::new)) {
+        Ok(Some(request)) => {
+            launch_route::open_launch_request(app, request);
+            return;
+        }
+        Ok(None) => {}
+        Err(error) => eprintln!("Ignoring forwarded launch route: {error}"),
+    }
+
+    let _ = window::open_window(
+        app,
+        None,
+        WindowName::Main,
+        None,
+        Some(launch.window_launch_payload()),
+    );
+}
+
+pub fn serve_forwarded_launches<R: Runtime>(app: &AppHandle<R>, listener: Listener) {
+    let app = app.clone();
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            match stream.and_then(read_forwarded_launch) {
+                Ok(launch) => open_forwarded_launch(&app, launch),
+                Err(error) => eprintln!("Failed to read forwarded launch: {error}"),
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ForwardedLaunch, InstanceRole, claim_single_instance, read_forwarded_launch};
+    use interprocess::local_socket::prelude::*;
+
+    fn launch(args: &[&str]) -> ForwardedLaunch {
+        ForwardedLaunch {
+            args: args.iter().map(|arg| arg.to_string()).collect(),
+            cwd: Some("/home/user/project".to_string()),
+        }
+    }
+
+    #[test]
+    fn second_launch_is_forwarded_to_the_primary_instance() {
+        let identifier = format!("app-single-instance-test-{}", std::process::id());
+        let InstanceRole::Primary(listener) =
+            claim_single_instance(&identifier, &launch(&[])).expect("failed to claim instance")
+        else {
+            panic!("first launch must become the primary instance");
+        };
+
+        let server = std::thread::spawn(move || {
+            let stream = listener
+                .incoming()
+                .next()
+                .expect("listener closed")
+                .expect("failed to accept forwarded launch");
+            read_forwarded_launch(stream)
+        });
+
+        let forwarded = launch(&["--open", "notes.md"]);
+        assert!(matches!(
+            claim_single_instance(&identifier, &forwarded),
+            Ok(InstanceRole::Forwarded)
+        ));
+        assert_eq!(
+            server
+                .join()
+                .expect("server thread panicked")
+                .expect("failed to read forwarded launch"),
+            forwarded
+        );
+    }
+
+    #[test]
+    fn forwarded_launch_becomes_main_window_payload() {
+        let payload = launch(&["--open", "notes.md"]).window_launch_payload();
+
+        assert_eq!(payload.route, None);
+        assert_eq!(
+            payload.params.get("cwd").map(String::as_str),
+            Some("/home/user/project")
+        );
+        assert_eq!(
+            payload.params.get("args").map(String::as_str),
+            Some(r#"["--open","notes.md"]"#)
+        );
+    }
+}
@@ -0,0 +1,188 @@
+use tauri::menu::{Menu, MenuEvent, MenuItem, PredefinedMenuItem};
+use tauri::tray::{TrayIcon, TrayIconBuilder, TrayIconEvent};
+use tauri::{AppHandle, Manager, Runtime};
+use tauri_specta::Event;
+
+use super::event::WindowLifecycleEvent;
+use super::window::{self, WindowName};
+
+const TRAY_ID: &str = "main";
+const WINDOW_MENU_ID_PREFIX: &str = "tray:window:";
+const NEW_WINDOW_MENU_ID: &str = "tray:new-window";
+const QUIT_MENU_ID: &str = "tray:quit";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum TrayMenuAction {
+    FocusWindow(String),
+    NewWindow,
+    Quit,
+}
+
+fn tray_menu_action(id: &str) -> Option<TrayMenuAction> {
+    match id {
+        NEW_WINDOW_MENU_ID => Some(TrayMenuAction::NewWindow),
+        QUIT_MENU_ID => Some(TrayMenuAction::Quit),
+        _ => id
+            .strip_prefix(WINDOW_MENU_ID_PREFIX)
+            .filter(|label| !label.is_empty())
+            .map(|label| TrayMenuAction::FocusWindow(label.to_string())),
+    }
+}
+
+fn tray_window_item_text(title: &str, label: &str) -> String {
+    if title.is_empty() || title == label {
+        return label.to_string();
+    }
+
+    format!("{title} ({label})")
+}
+
+/// Lists the open user windows, hidden ones included, since the tray is how a
+/// hidden window is found again. A window that is being destroyed may still be
+/// registered with the app, so it is skipped explicitly.
+fn build_tray_menu<R: Runtime>(
+    app: &AppHandle<R>,
+    destroyed_label: Option<&str>,
+) -> tauri::Result<Menu<R>> {
+    let menu = Menu::new(app)?;
+    let windows = window::user_windows(app)
+        .into_iter()
+        .filter(|window| Some(window.label()) != destroyed_label)
+        .collect::<Vec<_>>();
+
+    for window in &windows {
+        let label = window.label();
+        let title = window.title().unwrap_or_default();
+        menu.append(&MenuItem::with_id(
+            app,
+            format!("{WINDOW_MENU_ID_PREFIX}{label}"),
+            tray_window_item_text(&title, label),
+            true,
+            None::<&str>,
+        )?)?;
+    }
+    if !windows.is_empty() {
+        menu.append(&PredefinedMenuItem::separator(app)?)?;
+    }
+
+    menu.append(&MenuItem::with_id(
+        app,
+        NEW_WINDOW_MENU_ID,
+        "New Window",
+        true,
+        None::<&str>,
+    )?)?;
+    menu.append(&PredefinedMenuItem::separator(app)?)?;
+    menu.append(&MenuItem::with_id(
+        app,
+        QUIT_MENU_ID,
+        "Quit",
+        true,
+        None::<&str>,
+    )?)?;
+    Ok(menu)
+}
+
+fn refresh_tray_menu<R: Runtime>(app: &AppHandle<R>, destroyed_label: Option<&str>) {
+    let Some(tray) = app.tray_by_id(TRAY_ID) else {
+        return;
+    };
+
+    match build_tray_menu(app, destroyed_label) {
+        Ok(menu) => {
+            if let Err(error) = tray.set_menu(Some(menu)) {
+                eprintln!("Failed to update tray menu: {error}");
+            }
+        }
+        Err(error) => eprintln!("Failed to build tray menu: {error}"),
+    }
+}
+
+fn handle_tray_menu_event<R: Runtime>(app: &AppHandle<R>, event: MenuEvent) {
+    match tray_menu_action(event.id().as_ref()) {
+        Some(TrayMenuAction::FocusWindow(label)) => {
+            if let Some(window) = app.get_webview_window(&label) {
+                window::activate_window(&window);
+            }
+        }
+        Some(TrayMenuAction::NewWindow) => {
+            // Building a webview from a menu handler blocks the event loop on
+            // Windows, so the window is opened off the main thread.
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                let _ = window::open_window(&app, None, WindowName::Main, None, None);
+            });
+        }
+        Some(TrayMenuAction::Quit) => window::begin_graceful_shutdown(app, None),
+        None => {}
+    }
+}
+
+/// Window titles can change without any event, so the menu is also rebuilt
+/// when the pointer reaches the icon. Linux reports no tray icon events; there
+/// the list follows lifecycle events only.
+fn handle_tray_icon_event<R: Runtime>(tray: &TrayIcon<R>, event: TrayIconEvent) {
+    if let TrayIconEvent::Enter { .. } | TrayIconEvent::Click { .. } = event {
+        refresh_tray_menu(tray.app_handle(), None);
+    }
+}
+
+/// Installs the tray icon. Its window list is rebuilt from lifecycle events, so
+/// the events must be mounted first.
+pub fn install_tray<R: Runtime>(app: &AppHandle<R>) -> tauri::Result<()> {
+    let menu = build_tray_menu(app, None)?;
+    let mut builder = TrayIconBuilder::with_id(TRAY_ID)
+        .menu(&menu)
+        .tooltip(&app.package_info().name)
+        .on_menu_event(handle_tray_menu_event)
+        .on_tray_icon_event(handle_tray_icon_event);
+    if let Some(icon) = app.default_window_icon() {
+        builder = builder.icon(icon.clone());
+    }
+    builder.build(app)?;
+
+    let handle = app.clone();
+    WindowLifecycleEvent::listen(app, move |event| match event.payload {
+        WindowLifecycleEvent::Created { .. }
+        | WindowLifecycleEvent::Promoted { .. }
+        | WindowLifecycleEvent::VisibilityChanged { .. } => {
+            refresh_tray_menu(&handle, None);
+        }
+        WindowLifecycleEvent::Destroyed { label, .. } => {
+            refresh_tray_menu(&handle, Some(&label));
+        }
+        _ => {}
+    });
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{TrayMenuAction, tray_menu_action, tray_window_item_text};
+
+    #[test]
+    fn menu_ids_map_to_tray_actions() {
+        assert_eq!(
+            tray_menu_action("tray:window:main-1"),
+            Some(TrayMenuAction::FocusWindow("main-1".to_string()))
+        );
+        assert_eq!(
+            tray_menu_action("tray:new-window"),
+            Some(TrayMenuAction::NewWindow)
+        );
+        assert_eq!(tray_menu_action("tray:quit"), Some(TrayMenuAction::Quit));
+    }
+
+    #[test]
+    fn unknown_or_empty_menu_ids_are_ignored() {
+        assert_eq!(tray_menu_action("tray:window:"), None);
+        assert_eq!(tray_menu_action("quit"), None);
+    }
+
+    #[test]
+    fn window_items_show_the_label_when_it_differs_from_the_title() {
+        assert_eq!(tray_window_item_text("main", "main"), "main");
+        assert_eq!(tray_window_item_text("main", "main-1"), "main (main-1)");
+        assert_eq!(tray_window_item_text("", "main-2"), "main-2");
+    }
+}
@@ -1,13 +1,25 @@
 use serde::{Deserialize, Serialize};
 use specta::Type;
 use std::collections::{HashMap, HashSet};
-use std::sync::{Mutex, OnceLock};
-use tauri::{AppHandle, Emitter, Manager, WebviewWindow};
+use std::sync::{Mutex, MutexGuard, OnceLock};
+use std::time::{Duration, Instant};
+use tauri::ipc::Channel;
+use tauri::webview::PageLoadEvent;
+use tauri::{AppHandle, Emitter, Manager, Monitor, Runtime, State, WebviewWindow, Window};
 use tauri::{LogicalSize, PhysicalPosition, PhysicalSize, Position, Size};
 use tauri::{WebviewUrl, WebviewWindowBuilder};
+use tauri_specta::Event;
 
+use super::error::AppError;
+use super::event::{WindowLifecycleEvent, WindowStateEvent};
 #[cfg(target_os = "macos")]
 use super::macos_titlebar::FullscreenStateManager;
+use super::session;
+use super::shutdown;
+use super::window_bus;
+use super::window_kinds;
+pub use super::window_kinds::WindowName;
+use super::window_state::{self, WindowStateRestore};
 #[cfg(target_os = "macos")]
 use std::cell::RefCell;
 use std::fmt;
@@ -25,6 +37,8 @@ pub struct WindowKindInfo {
     pub is_primary_window: bool,
     pub is_user_window: bool,
     pub is_prepared_window: bool,
+    /// Window this one was opened from and closes with, for owned kinds.
+    pub owner_label: Option<String>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -36,18 +50,67 @@ struct WindowIdentity {
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum UserWindowPolicy {
+pub enum UserWindowPolicy {
     PrimaryAndIndexed,
     Never,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
-struct WindowDescriptor {
+/// How many windows of a kind may be open at once.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WindowInstancePolicy {
+    /// Every open request builds another window.
+    Multiple,
+    /// An open request focuses the open window and hands it the payload.
+    Singleton,
+}
+
+/// What closing a window of a kind means for the process.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WindowClosePolicy {
+    /// Closing the last visible user window shuts the app down gracefully.
+    ExitOnLastWindow,
+    /// The close is turned into a hide, so the window can be shown again.
+    HideWindow,
+    /// The window is destroyed, but the process stays alive without windows.
+    KeepRunning,
+}
+
+/// Where a window without saved geometry opens relative to the window that
+/// opened it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WindowPlacement {
+    /// Keeps the default position and only moves onto the opener's monitor
+    /// when the window would be off-screen there.
+    OpenerMonitor,
+    /// Offsets the window from its opener by `step` logical pixels on both
+    /// axes, wrapping to the work area's origin when it would leave it.
+    Cascade { step: u32 },
+}
+
+/// How the `CloseRequested` handler answers a close request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WindowCloseAction {
+    Close,
+    Hide,
+    Exit,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct WindowDescriptor {
     name: WindowName,
-    base_label: &'static str,
-    title: &'static str,
+    base_label: String,
+    title: String,
+    route: String,
     user_window_policy: UserWindowPolicy,
+    instance_policy: WindowInstancePolicy,
+    close_policy: WindowClosePolicy,
+    placement: WindowPlacement,
+    launch_target: Option<String>,
+    owned_by_opener: bool,
+    modal: bool,
+    restores_session: bool,
     prewarm_enabled: bool,
+    prewarm_pool_size: usize,
     uses_primary_window_setup: bool,
     default_width: f64,
     default_height: f64,
@@ -56,136 +119,414 @@ struct WindowDescriptor {
 }
 
 impl WindowDescriptor {
-    fn is_primary_label(self, label: &str) -> bool {
+    pub fn new(name: WindowName, base_label: impl Into<String>) -> Self {
+        let base_label = base_label.into();
+        Self {
+            name,
+            title: base_label.clone(),
+            base_label,
+            route: "index.html".to_string(),
+            user_window_policy: UserWindowPolicy::Never,
+            instance_policy: WindowInstancePolicy::Multiple,
+            close_policy: WindowClosePolicy::ExitOnLastWindow,
+            placement: WindowPlacement::OpenerMonitor,
+            launch_target: None,
+            owned_by_opener: false,
+            modal: false,
+            restores_session: false,
+            prewarm_enabled: false,
+            prewarm_pool_size: 1,
+            uses_primary_window_setup: false,
+            default_width: 1400.0,
+            default_height: 750.0,
+            min_width: 768.0,
+            min_height: 500.0,
+        }
+    }
+
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.title = title.into();
+        self
+    }
+
+    pub fn route(mut self, route: impl Into<String>) -> Self {
+        self.route = route.into();
+        self
+    }
+
+    pub fn user_window_policy(mut self, policy: UserWindowPolicy) -> Self {
+        self.user_window_policy = policy;
+        self
+    }
+
+    pub fn instance_policy(mut self, policy: WindowInstancePolicy) -> Self {
+        self.instance_policy = policy;
+        self
+    }
+
+    pub fn close_policy(mut self, policy: WindowClosePolicy) -> Self {
+        self.close_policy = policy;
+        self
+    }
+
+    pub fn placement(mut self, placement: WindowPlacement) -> Self {
+        self.placement = placement;
+        self
+    }
+
+    /// Name used by deep links and `--window` to open this kind. Kinds
+    /// without one cannot be opened from outside the app.
+    pub fn launch_target(mut self, target: impl Into<String>) -> Self {
+        self.launch_target = Some(target.into());
+        self
+    }
+
+    /// Windows of an owned kind stay above the window that opened them and
+    /// close with it. The owner is set when the window is built, so owned
+    /// kinds can be neither prewarmed nor user windows.
+    pub fn owned_by_opener(mut self, enabled: bool) -> Self {
+        self.owned_by_opener = enabled;
+        self
+    }
+
+    /// Blocks input to the owner until the owned window is gone.
+    pub fn modal(mut self, enabled: bool) -> Self {
+        self.modal = enabled;
+        self
+    }
+
+    /// Open windows of this kind are recorded with their payload and geometry
+    /// and reopened on the next start. Only user windows can opt in.
+    pub fn restore_session(mut self, enabled: bool) -> Self {
+        self.restores_session = enabled;
+        self
+    }
+
+    pub fn prewarm(mut self, enabled: bool) -> Self {
+        self.prewarm_enabled = enabled;
+        self
+    }
+
+    pub fn prewarm_pool_size(mut self, size: usize) -> Self {
+        self.prewarm_pool_size = size;
+        self
+    }
+
+    pub fn primary_window_setup(mut self, enabled: bool) -> Self {
+        self.uses_primary_window_setup = enabled;
+        self
+    }
+
+    pub fn size(mut self, width: f64, height: f64) -> Self {
+        self.default_width = width;
+        self.default_height = height;
+        self
+    }
+
+    pub fn min_size(mut self, width: f64, height: f64) -> Self {
+        self.min_width = width;
+        self.min_height = height;
+        self
+    }
+
+    fn is_primary_label(&self, label: &str) -> bool {
         label == self.base_label
     }
 
-    fn matches_label(self, label: &str) -> bool {
+    fn matches_label(&self, label: &str) -> bool {
         if self.is_primary_label(label) {
             return true;
         }
 
-        is_numeric_indexed_label(self.base_label, label)
-            || label == self.canonical_prewarm_label()
+        is_numeric_indexed_label(&self.base_label, label) || self.is_prewarm_label(label)
+    }
+
+    fn is_prewarm_label(&self, label: &str) -> bool {
+        label == self.canonical_prewarm_label()
             || label.starts_with(&format!("{}-", self.canonical_prewarm_label()))
     }
 
-    fn canonical_prewarm_label(self) -> String {
+    fn canonical_prewarm_label(&self) -> String {
         format!("{}-prewarm", self.base_label)
     }
 
-    fn is_user_window_label(self, label: &str) -> bool {
+    fn is_user_window_label(&self, label: &str) -> bool {
         match self.user_window_policy {
             UserWindowPolicy::PrimaryAndIndexed => {
-                self.is_primary_label(label) || is_numeric_indexed_label(self.base_label, label)
+                self.is_primary_label(label) || is_numeric_indexed_label(&self.base_label, label)
             }
             UserWindowPolicy::Never => false,
         }
     }
 }
 
-const WINDOW_DESCRIPTORS: [WindowDescriptor; 2] = [
-    WindowDescriptor {
-        name: WindowName::Main,
-        base_label: "main",
-        title: "main",
-        user_window_policy: UserWindowPolicy::PrimaryAndIndexed,
-        prewarm_enabled: true,
-        uses_primary_window_setup: true,
-        default_width: 1400.0,
-        default_height: 750.0,
-        min_width: 768.0,
-        min_height: 500.0,
-    },
-    WindowDescriptor {
-        name: WindowName::Support,
-        base_label: "support",
-        title: "support",
-        user_window_policy: UserWindowPolicy::Never,
-        prewarm_enabled: false,
-        uses_primary_window_setup: false,
-        default_width: 1400.0,
-        default_height: 750.0,
-        min_width: 768.0,
-        min_height: 500.0,
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WindowDescriptorError {
+    AlreadyInstalled,
+    EmptyBaseLabel(WindowName),
+    EmptyRoute(WindowName),
+    EmptyPrewarmPool(WindowName),
+    DuplicateWindow(WindowName),
+    MissingWindow(WindowName),
+    OverlappingBaseLabels {
+        label: String,
+        conflicting_label: String,
     },
-];
-
-fn window_descriptor(name: WindowName) -> &'static WindowDescriptor {
-    WINDOW_DESCRIPTORS
-        .iter()
-        .find(|descriptor| descriptor.name == name)
-        .expect("window descriptor missing")
+    InvalidSize(WindowName),
+    EmptyLaunchTarget(WindowName),
+    DuplicateLaunchTarget(String),
+    EmptyCascadeStep(WindowName),
+    ModalWithoutOwner(WindowName),
+    OwnedUserWindow(WindowName),
+    OwnedPrewarmWindow(WindowName),
+    OwnedHiddenWindow(WindowName),
+    OwnedSingletonWindow(WindowName),
+    SessionRestoreWithoutUserWindow(WindowName),
 }
 
-fn window_descriptor_for_label(label: &str) -> Option<&'static WindowDescriptor> {
-    WINDOW_DESCRIPTORS
-        .iter()
-        .find(|descriptor| descriptor.matches_label(label))
+impl fmt::Display for WindowDescriptorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::AlreadyInstalled => f.write_str("window descriptors are already installed"),
+            Self::EmptyBaseLabel(name) => write!(f, "window {name} has an empty base label"),
+            Self::EmptyRoute(name) => write!(f, "window {name} has an empty route"),
+            Self::EmptyPrewarmPool(name) => {
+                write!(f, "window {name} enables prewarm with an empty pool")
+            }
+            Self::DuplicateWindow(name) => write!(f, "window {name} is registered twice"),
+            Self::MissingWindow(name) => write!(f, "window {name} has no descriptor"),
+            Self::OverlappingBaseLabels {
+                label,
+                conflicting_label,
+            } => write!(
+                f,
+                "window base label {label} overlaps with labels derived from {conflicting_label}"
+            ),
+            Self::InvalidSize(name) => {
+                write!(f, "window {name} has a default size below its minimum size")
+            }
+            Self::EmptyLaunchTarget(name) => write!(f, "window {name} has an empty launch target"),
+            Self::DuplicateLaunchTarget(target) => {
+                write!(f, "launch target {target} is registered twice")
+            }
+            Self::EmptyCascadeStep(name) => {
+                write!(f, "window {name} cascades with a zero step")
+            }
+            Self::ModalWithoutOwner(name) => {
+                write!(f, "window {name} is modal but not owned by its opener")
+            }
+            Self::OwnedUserWindow(name) => {
+                write!(
+                    f,
+                    "window {name} is owned by its opener but counts as a user window"
+                )
+            }
+            Self::OwnedPrewarmWindow(name) => {
+                write!(
+                    f,
+                    "window {name} is owned by its opener and cannot be prewarmed"
+                )
+            }
+            Self::OwnedHiddenWindow(name) => {
+                write!(
+                    f,
+                    "window {name} is owned by its opener and cannot hide on close"
+                )
+            }
+            Self::OwnedSingletonWindow(name) => {
+                write!(
+                    f,
+                    "window {name} is owned by its opener and cannot be a singleton"
+                )
+            }
+            Self::SessionRestoreWithoutUserWindow(name) => {
+                write!(
+                    f,
+                    "window {name} restores its session but is not a user window"
+                )
+            }
+        }
+    }
 }
 
-fn is_label_reserved_for_prepared_window(label: &str) -> bool {
-    let inventory = prepared_window_inventory()
-        .lock()
-        .expect("prepared window inventory poisoned");
-    inventory.values().any(|state| state.label == label)
+impl std::error::Error for WindowDescriptorError {}
+
+#[derive(Debug, Default)]
+pub struct WindowDescriptorRegistryBuilder {
+    descriptors: Vec<WindowDescriptor>,
 }
 
-fn classify_window_identity(label: &str) -> WindowIdentity {
-    let descriptor = window_descriptor_for_label(label).copied();
-    let window = descriptor.map(|descriptor| descriptor.name);
-    let is_primary_window = descriptor.is_some_and(|descriptor| descriptor.is_primary_label(label));
-    let is_promoted_user_window = promoted_user_window_labels()
-        .lock()
-        .expect("promoted user window labels poisoned")
-        .contains(label);
-    let is_prepared_window = is_label_reserved_for_prepared_window(label);
-    let is_user_window = descriptor
-        .is_some_and(|descriptor| descriptor.is_user_window_label(label))
-        || is_promoted_user_window;
+impl WindowDescriptorRegistryBuilder {
+    pub fn window(mut self, descriptor: WindowDescriptor) -> Self {
+        self.descriptors.push(descriptor);
+        self
+    }
 
-    WindowIdentity {
-        window,
-        is_primary_window,
-        is_user_window,
-        is_prepared_window,
+    pub fn build(self) -> Result<WindowDescriptorRegistry, WindowDescriptorError> {
+        validate_window_descriptors(&self.descriptors)?;
+        Ok(WindowDescriptorRegistry {
+            descriptors: self.descriptors,
+        })
     }
 }
 
-fn window_kind_info_for_label(label: &str) -> WindowKindInfo {
-    let identity = classify_window_identity(label);
+#[derive(Debug)]
+pub struct WindowDescriptorRegistry {
+    descriptors: Vec<WindowDescriptor>,
+}
+
+fn validate_window_descriptors(
+    descriptors: &[WindowDescriptor],
+) -> Result<(), WindowDescriptorError> {
+    for (index, descriptor) in descriptors.iter().enumerate() {
+        if descriptor.base_label.is_empty() {
+            return Err(WindowDescriptorError::EmptyBaseLabel(descriptor.name));
+        }
+
+        if descriptor.route.is_empty() {
+            return Err(WindowDescriptorError::EmptyRoute(descriptor.name));
+        }
+
+        if descriptor.prewarm_enabled && descriptor.prewarm_pool_size == 0 {
+            return Err(WindowDescriptorError::EmptyPrewarmPool(descriptor.name));
+        }
+
+        if descriptor.default_width < descriptor.min_width
+            || descriptor.default_height < descriptor.min_height
+        {
+            return Err(WindowDescriptorError::InvalidSize(descriptor.name));
+        }
+
+        if descriptor.placement == (WindowPlacement::Cascade { step: 0 }) {
+            return Err(WindowDescriptorError::EmptyCascadeStep(descriptor.name));
+        }
+
+        if descriptor.modal && !descriptor.owned_by_opener {
+            return Err(WindowDescriptorError::ModalWithoutOwner(descriptor.name));
+        }
+
+        if descriptor.owned_by_opener && descriptor.user_window_policy != UserWindowPolicy::Never {
+            return Err(WindowDescriptorError::OwnedUserWindow(descriptor.name));
+        }
+
+        if descriptor.owned_by_opener && descriptor.prewarm_enabled {
+            return Err(WindowDescriptorError::OwnedPrewarmWindow(descriptor.name));
+        }
+
+        if descriptor.owned_by_opener && descriptor.close_policy == WindowClosePolicy::HideWindow {
+            return Err(WindowDescriptorError::OwnedHiddenWindow(descriptor.name));
+        }
+
+        // An open singleton is handed to whichever window asks for it next,
+        // but it stays attached to the window that first opened it.
+        if descriptor.owned_by_opener
+            && descriptor.instance_policy == WindowInstancePolicy::Singleton
+        {
+            return Err(WindowDescriptorError::OwnedSingletonWindow(descriptor.name));
+        }
+
+        if descriptor.restores_session && descriptor.user_window_policy == UserWindowPolicy::Never {
+            return Err(WindowDescriptorError::SessionRestoreWithoutUserWindow(
+                descriptor.name,
+            ));
+        }
+
+        if descriptor.launch_target.as_deref() == Some("") {
+            return Err(WindowDescriptorError::EmptyLaunchTarget(descriptor.name));
+        }
+
+        for other in &descriptors[..index] {
+            if other.name == descriptor.name {
+                return Err(WindowDescriptorError::DuplicateWindow(descriptor.name));
+            }
+
+            if let (Some(target), Some(other_target)) =
+                (&descriptor.launch_target, &other.launch_target)
+                && target.eq_ignore_ascii_case(other_target)
+            {
+                return Err(WindowDescriptorError::DuplicateLaunchTarget(target.clone()));
+            }
+
+            if other.matches_label(&descriptor.base_label)
+                || descriptor.matches_label(&other.base_label)
+            {
+                return Err(WindowDescriptorError::OverlappingBaseLabels {
+                    label: descriptor.base_label.clone(),
+                    conflicting_label: other.base_label.clone(),
+                });
+            }
+        }
+    }
 
-    WindowKindInfo {
-        window: identity.window,
-        label: label.to_string(),
-        is_primary_window: identity.is_primary_window,
-        is_user_window: identity.is_user_window,
-        is_prepared_window: identity.is_prepared_window,
+    for name in WindowName::ALL {
+        if !descriptors.iter().any(|descriptor| descriptor.name == name) {
+            return Err(WindowDescriptorError::MissingWindow(name));
+        }
     }
+
+    Ok(())
 }
 
-pub fn window_kind_from_label(label: &str) -> Option<WindowName> {
-    window_descriptor_for_label(label).map(|descriptor| descriptor.name)
+fn registered_window_descriptors() -> &'static OnceLock<WindowDescriptorRegistry> {
+    static WINDOW_DESCRIPTORS: OnceLock<WindowDescriptorRegistry> = OnceLock::new();
+    &WINDOW_DESCRIPTORS
 }
 
-#[tauri::command]
-#[specta::specta]
-pub fn get_window_kind(window: WebviewWindow) -> WindowKindInfo {
-    window_kind_info_for_label(window.label())
+pub fn install_window_descriptors(
+    registry: WindowDescriptorRegistry,
+) -> Result<(), WindowDescriptorError> {
+    registered_window_descriptors()
+        .set(registry)
+        .map_err(|_| WindowDescriptorError::AlreadyInstalled)
+}
+
+fn window_descriptor_registry() -> &'static [WindowDescriptor] {
+    &registered_window_descriptors()
+        .get_or_init(|| {
+            window_kinds::window_descriptors()
+                .build()
+                .expect("default window descriptors invalid")
+        })
+        .descriptors
 }
 
-#[allow(dead_code)]
-pub fn is_user_window_label(label: &str) -> bool {
-    should_label_resolve_as_user_window(label)
+fn find_window_descriptor(name: WindowName) -> Option<&'static WindowDescriptor> {
+    window_descriptor_registry()
+        .iter()
+        .find(|descriptor| descriptor.name == name)
 }
 
-pub fn should_label_resolve_as_user_window(label: &str) -> bool {
-    classify_window_identity(label).is_user_window
+fn window_descriptor(name: WindowName) -> &'static WindowDescriptor {
+    find_window_descriptor(name).expect("window descriptor missing")
 }
 
-#[cfg(test)]
-fn classify_window_labels<'a>(labels: impl IntoIterator<Item = &'a str>) -> Vec<WindowKindInfo> {
-    labels.into_iter().map(window_kind_info_for_label).collect()
+fn window_descriptor_for_label(label: &str) -> Option<&'static WindowDescriptor> {
+    window_descriptor_registry()
+        .iter()
+        .find(|descriptor| descriptor.matches_label(label))
+}
+
+pub fn window_kind_from_label(label: &str) -> Option<WindowName> {
+    window_descriptor_for_label(label).map(|descriptor| descriptor.name)
+}
+
+/// Whether open windows of this kind are reopened on the next launch.
+pub fn restores_session(name: WindowName) -> bool {
+    find_window_descriptor(name).is_some_and(|descriptor| descriptor.restores_session)
+}
+
+/// Resolves the window part of a deep link or `--window` flag. Targets are
+/// matched without regard to ASCII case, since they are typed by hand.
+pub fn window_kind_from_launch_target(target: &str) -> Option<WindowName> {
+    window_descriptor_registry()
+        .iter()
+        .find(|descriptor| {
+            descriptor
+                .launch_target
+                .as_deref()
+                .is_some_and(|launch_target| launch_target.eq_ignore_ascii_case(target))
+        })
+        .map(|descriptor| descriptor.name)
 }
 
 fn is_numeric_indexed_label(base_label: &str, label: &str) -> bool {
@@ -197,23 +538,9 @@ fn is_numeric_indexed_label(base_label: &str, label: &str) -> bool {
     !suffix.is_empty() && suffix.chars().all(|character| character.is_ascii_digit())
 }
 
-fn should_exit_on_window_close_after_visible_user_count(
-    closing_label: &str,
-    visible_user_window_count_before_close: usize,
-) -> bool {
-    if is_graceful_shutdown_in_progress() {
-        return false;
-    }
-
-    if !should_label_resolve_as_user_window(closing_label) {
-        return false;
-    }
-
-    visible_user_window_count_before_close.saturating_sub(1) == 0
-}
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum PreparedWindowReadiness {
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Type)]
+pub enum PreparedWindowReadiness {
+    Scheduled,
     Created,
     Ready,
 }
@@ -222,1455 +549,4676 @@ enum PreparedWindowReadiness {
 struct PreparedWindowState {
     label: String,
     readiness: PreparedWindowReadiness,
+    since: Instant,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
-struct PreparedWindowDisposition {
-    label: String,
-    removed_from_inventory: bool,
-}
-
-#[derive(Debug, Clone, PartialEq, Eq)]
-struct GracefulShutdownProgress {
-    pending_labels: HashSet<String>,
-}
+impl PreparedWindowState {
+    fn new(label: String, readiness: PreparedWindowReadiness) -> Self {
+        Self {
+            label,
+            readiness,
+            since: Instant::now(),
+        }
+    }
 
-fn prepared_window_inventory() -> &'static Mutex<HashMap<WindowName, PreparedWindowState>> {
-    static PREPARED_WINDOWS: OnceLock<Mutex<HashMap<WindowName, PreparedWindowState>>> =
-        OnceLock::new();
-    PREPARED_WINDOWS.get_or_init(|| Mutex::new(HashMap::new()))
+    fn transition(&mut self, readiness: PreparedWindowReadiness) {
+        self.readiness = readiness;
+        self.since = Instant::now();
+    }
 }
 
-fn warm_window_owners() -> &'static Mutex<HashMap<WindowName, HashSet<String>>> {
-    static WARM_WINDOW_OWNERS: OnceLock<Mutex<HashMap<WindowName, HashSet<String>>>> =
-        OnceLock::new();
-    WARM_WINDOW_OWNERS.get_or_init(|| Mutex::new(HashMap::new()))
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PreparedWindowRecycleReason {
+    ReadyTimeout,
+    Expired,
+    RendererReloaded,
 }
 
-fn promoted_user_window_labels() -> &'static Mutex<HashSet<String>> {
-    static PROMOTED_USER_WINDOW_LABELS: OnceLock<Mutex<HashSet<String>>> = OnceLock::new();
-    PROMOTED_USER_WINDOW_LABELS.get_or_init(|| Mutex::new(HashSet::new()))
+impl fmt::Display for PreparedWindowRecycleReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::ReadyTimeout => "renderer never reported ready",
+            Self::Expired => "prepared window outlived its ttl",
+            Self::RendererReloaded => "renderer reloaded",
+        })
+    }
 }
 
-fn graceful_shutdown_state() -> &'static Mutex<Option<GracefulShutdownProgress>> {
-    static GRACEFUL_SHUTDOWN_PROGRESS: OnceLock<Mutex<Option<GracefulShutdownProgress>>> =
-        OnceLock::new();
-    GRACEFUL_SHUTDOWN_PROGRESS.get_or_init(|| Mutex::new(None))
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct PreparedWindowDisposition {
+    labels: Vec<String>,
+    removed_from_inventory: bool,
 }
 
-fn is_graceful_shutdown_in_progress() -> bool {
-    graceful_shutdown_state()
-        .lock()
-        .expect("graceful shutdown state poisoned")
-        .is_some()
+/// Upper bound on hidden prepared webviews across every window kind.
+const MAX_PREPARED_WINDOWS: usize = 4;
+const PREPARED_WINDOW_READY_TIMEOUT: Duration = Duration::from_secs(20);
+const PREPARED_WINDOW_TTL: Duration = Duration::from_secs(30 * 60);
+const PREPARED_WINDOW_SUPERVISOR_INTERVAL: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Type)]
+pub struct PrewarmPoolMetrics {
+    pub window: WindowName,
+    pub pool_hits: u32,
+    pub cold_opens: u32,
+    pub recycled: u32,
 }
 
-#[cfg(test)]
-fn try_begin_graceful_shutdown() -> bool {
-    start_graceful_shutdown_tracking(std::iter::empty::<String>())
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PrewarmPoolOutcome {
+    Hit,
+    ColdOpen,
+    Recycled,
 }
 
-#[cfg(test)]
-fn reset_graceful_shutdown_state() {
-    let mut shutdown = graceful_shutdown_state()
-        .lock()
-        .expect("graceful shutdown state poisoned");
-    *shutdown = None;
-}
-
-fn start_graceful_shutdown_tracking(labels: impl IntoIterator<Item = String>) -> bool {
-    let mut shutdown = graceful_shutdown_state()
-        .lock()
-        .expect("graceful shutdown state poisoned");
-    if shutdown.is_some() {
-        return false;
-    }
-
-    *shutdown = Some(GracefulShutdownProgress {
-        pending_labels: labels.into_iter().collect(),
-    });
-    true
+/// Data handed from `create_window` to the window it opens. The renderer
+/// fetches it once through `take_window_launch_payload`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, Type)]
+pub struct WindowLaunchPayload {
+    pub route: Option<String>,
+    pub params: HashMap<String, String>,
 }
 
-#[cfg(test)]
-fn graceful_shutdown_pending_labels() -> Vec<String> {
-    let shutdown = graceful_shutdown_state()
-        .lock()
-        .expect("graceful shutdown state poisoned");
-    let Some(progress) = shutdown.as_ref() else {
-        return Vec::new();
-    };
-
-    let mut labels = progress.pending_labels.iter().cloned().collect::<Vec<_>>();
-    labels.sort();
-    labels
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum GracefulShutdownProgress {
+    /// User windows may still veto; nothing has been closed yet.
+    AwaitingVerdicts {
+        request_id: u32,
+        closing_label: Option<String>,
+        awaiting_labels: HashSet<String>,
+    },
+    /// Every window is closing and the app exits once the last one is gone.
+    Closing { pending_labels: HashSet<String> },
 }
 
-fn graceful_shutdown_target_labels(
-    labels: impl IntoIterator<Item = String>,
-    closing_label: &str,
-) -> Vec<String> {
-    labels
-        .into_iter()
-        .filter(|label| label != closing_label)
-        .collect()
+/// A renderer's answer to `GracefulShutdownRequested`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
+pub enum ShutdownVerdict {
+    Proceed,
+    Veto,
 }
 
-fn add_warm_window_owner(name: WindowName, label: &str) -> bool {
-    let mut owners = warm_window_owners()
-        .lock()
-        .expect("warm window owners poisoned");
-    owners.entry(name).or_default().insert(label.to_string())
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ShutdownVerdictOutcome {
+    Ignored,
+    Waiting,
+    Vetoed,
+    Approved,
 }
 
-fn remove_warm_window_owner(name: WindowName, label: &str) -> bool {
-    let mut owners = warm_window_owners()
-        .lock()
-        .expect("warm window owners poisoned");
-    let Some(labels) = owners.get_mut(&name) else {
-        return false;
-    };
+const DEFAULT_SHUTDOWN_VERDICT_TIMEOUT: Duration = Duration::from_secs(5);
 
-    labels.remove(label);
-    if labels.is_empty() {
-        owners.remove(&name);
-        return false;
-    }
+const DEFAULT_POSITION_UPDATE_INTERVAL_MS: u32 = 16;
+const MIN_POSITION_UPDATE_INTERVAL_MS: u32 = 8;
+const MAX_POSITION_UPDATE_INTERVAL_MS: u32 = 1000;
 
-    true
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct OwnedWindow {
+    owner_label: String,
+    modal: bool,
 }
 
-fn remove_warm_window_owner_label(label: &str) -> Vec<WindowName> {
-    let mut owners = warm_window_owners()
-        .lock()
-        .expect("warm window owners poisoned");
-    let mut emptied_targets = Vec::new();
-
-    owners.retain(|name, labels| {
-        labels.remove(label);
-        let keep = !labels.is_empty();
-        if !keep {
-            emptied_targets.push(*name);
-        }
-        keep
-    });
-
-    emptied_targets.sort_by_key(WindowName::as_str);
-    emptied_targets
+/// What a destroyed window leaves for its owned windows and its owner.
+#[derive(Debug, Default, PartialEq, Eq)]
+struct OwnedWindowRelease {
+    /// Windows the destroyed window owned; they close with it.
+    owned_labels: Vec<String>,
+    /// Owner whose input was blocked by the destroyed window and no other.
+    unblocked_owner_label: Option<String>,
 }
 
-fn has_warm_window_owner(name: WindowName) -> bool {
-    warm_window_owners()
-        .lock()
-        .expect("warm window owners poisoned")
-        .get(&name)
-        .is_some_and(|labels| !labels.is_empty())
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct LiveWindowSnapshot {
+    pub kind: WindowKindInfo,
+    pub visible: bool,
 }
 
-fn active_warm_window_targets() -> Vec<WindowName> {
-    let owners = warm_window_owners()
-        .lock()
-        .expect("warm window owners poisoned");
-    let mut targets = owners
-        .iter()
-        .filter_map(|(name, labels)| (!labels.is_empty()).then_some(*name))
-        .collect::<Vec<_>>();
-    targets.sort_by_key(WindowName::as_str);
-    targets
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Type)]
+pub struct PreparedWindowSnapshot {
+    pub window: WindowName,
+    pub label: String,
+    pub readiness: PreparedWindowReadiness,
+    /// Milliseconds spent in the current readiness state.
+    pub readiness_age_ms: u32,
 }
 
-#[tauri::command]
-#[specta::specta]
-pub fn record_renderer_bootstrap_ready(window: WebviewWindow) {
-    let label = window.label().to_string();
-    let Some(name) = window_kind_from_label(&label) else {
-        return;
-    };
-
-    if !should_label_resolve_as_user_window(&label) {
-        let _ = mark_prepared_window_ready(name, &label);
-    }
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Type)]
+pub struct WarmWindowOwnersSnapshot {
+    pub window: WindowName,
+    pub owners: Vec<String>,
 }
 
-fn reserve_prepared_window(name: WindowName, label: String) {
-    let mut inventory = prepared_window_inventory()
-        .lock()
-        .expect("prepared window inventory poisoned");
-    inventory.insert(
-        name,
-        PreparedWindowState {
-            label,
-            readiness: PreparedWindowReadiness::Created,
-        },
-    );
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Type)]
+pub struct GracefulShutdownSnapshot {
+    /// User windows that have not answered the shutdown request yet.
+    pub awaiting_verdict_labels: Vec<String>,
+    /// Windows that are closing and still have to be destroyed.
+    pub pending_labels: Vec<String>,
 }
 
-fn promote_window_label_to_user_window(label: &str) {
-    let mut promoted_labels = promoted_user_window_labels()
-        .lock()
-        .expect("promoted user window labels poisoned");
-    promoted_labels.insert(label.to_string());
+/// Point-in-time copy of the lifecycle state, for diagnosing unexpected
+/// exits and slow window opens.
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct WindowRegistrySnapshot {
+    pub windows: Vec<LiveWindowSnapshot>,
+    pub prepared_windows: Vec<PreparedWindowSnapshot>,
+    pub warm_window_owners: Vec<WarmWindowOwnersSnapshot>,
+    pub graceful_shutdown: Option<GracefulShutdownSnapshot>,
 }
 
-fn demote_window_label_from_user_window(label: &str) {
-    let mut promoted_labels = promoted_user_window_labels()
-        .lock()
-        .expect("promoted user window labels poisoned");
-    promoted_labels.remove(label);
+#[derive(Debug, Default)]
+struct WindowRegistryState {
+    prepared_windows: HashMap<WindowName, Vec<PreparedWindowState>>,
+    prewarm_pool_metrics: HashMap<WindowName, PrewarmPoolMetrics>,
+    warm_window_owners: HashMap<WindowName, HashSet<String>>,
+    promoted_user_window_labels: HashSet<String>,
+    launch_payloads: HashMap<String, WindowLaunchPayload>,
+    /// Payload a window would reopen with when the session is restored. It
+    /// starts as the launch payload and the renderer replaces it as it
+    /// navigates.
+    session_payloads: HashMap<String, WindowLaunchPayload>,
+    owned_windows: HashMap<String, OwnedWindow>,
+    graceful_shutdown: Option<GracefulShutdownProgress>,
+    last_shutdown_request_id: u32,
+    /// Position subscription of each window, by subscription id.
+    position_subscriptions: HashMap<String, u32>,
+    last_position_subscription_id: u32,
+    /// Last window state each window was told about.
+    window_states: HashMap<String, WindowStateEvent>,
 }
 
-fn mark_prepared_window_ready(name: WindowName, label: &str) -> bool {
-    let mut inventory = prepared_window_inventory()
-        .lock()
-        .expect("prepared window inventory poisoned");
+impl WindowRegistryState {
+    fn is_label_reserved_for_prepared_window(&self, label: &str) -> bool {
+        self.prepared_windows
+            .values()
+            .flatten()
+            .any(|state| state.label == label)
+    }
 
-    let Some(state) = inventory.get_mut(&name) else {
-        return false;
-    };
+    fn classify_window_identity(&self, label: &str) -> WindowIdentity {
+        let descriptor = window_descriptor_for_label(label);
+        let window = descriptor.map(|descriptor| descriptor.name);
+        let is_primary_window =
+            descriptor.is_some_and(|descriptor| descriptor.is_primary_label(label));
+        let is_user_window = descriptor
+            .is_some_and(|descriptor| descriptor.is_user_window_label(label))
+            || self.promoted_user_window_labels.contains(label);
+
+        WindowIdentity {
+            window,
+            is_primary_window,
+            is_user_window,
+            is_prepared_window: self.is_label_reserved_for_prepared_window(label),
+        }
+    }
 
-    if state.label != label {
-        return false;
+    fn window_kind_info_for_label(&self, label: &str) -> WindowKindInfo {
+        let identity = self.classify_window_identity(label);
+
+        WindowKindInfo {
+            window: identity.window,
+            label: label.to_string(),
+            is_primary_window: identity.is_primary_window,
+            is_user_window: identity.is_user_window,
+            is_prepared_window: identity.is_prepared_window,
+            owner_label: self
+                .owned_windows
+                .get(label)
+                .map(|owned| owned.owner_label.clone()),
+        }
     }
 
-    state.readiness = PreparedWindowReadiness::Ready;
-    true
-}
+    fn release_owned_windows(&mut self, label: &str) -> OwnedWindowRelease {
+        let unblocked_owner_label = self
+            .owned_windows
+            .remove(label)
+            .filter(|owned| owned.modal)
+            .map(|owned| owned.owner_label)
+            .filter(|owner_label| {
+                !self
+                    .owned_windows
+                    .values()
+                    .any(|owned| owned.modal && owned.owner_label == *owner_label)
+            });
+
+        let mut owned_labels = self
+            .owned_windows
+            .iter()
+            .filter(|(_, owned)| owned.owner_label == label)
+            .map(|(owned_label, _)| owned_label.clone())
+            .collect::<Vec<_>>();
+        owned_labels.sort();
+        self.owned_windows
+            .retain(|_, owned| owned.owner_label != label);
 
-#[cfg(test)]
-fn discard_prepared_window(name: WindowName) -> bool {
-    let mut inventory = prepared_window_inventory()
-        .lock()
-        .expect("prepared window inventory poisoned");
-    inventory.remove(&name).is_some()
-}
-
-fn discard_prepared_window_state(name: WindowName) -> Option<PreparedWindowDisposition> {
-    let mut inventory = prepared_window_inventory()
-        .lock()
-        .expect("prepared window inventory poisoned");
-
-    if let Some(state) = inventory.remove(&name) {
-        return Some(PreparedWindowDisposition {
-            label: state.label,
-            removed_from_inventory: true,
-        });
+        OwnedWindowRelease {
+            owned_labels,
+            unblocked_owner_label,
+        }
     }
 
-    Some(PreparedWindowDisposition {
-        label: window_descriptor(name).canonical_prewarm_label(),
-        removed_from_inventory: false,
-    })
-}
-
-fn take_prepared_window(name: WindowName) -> Option<PreparedWindowState> {
-    let mut inventory = prepared_window_inventory()
-        .lock()
-        .expect("prepared window inventory poisoned");
-    let should_take = inventory
-        .get(&name)
-        .is_some_and(|state| state.readiness == PreparedWindowReadiness::Ready);
+    fn take_ready_prepared_window(&mut self, name: WindowName) -> Option<PreparedWindowState> {
+        let slots = self.prepared_windows.get_mut(&name)?;
+        let index = slots
+            .iter()
+            .position(|state| state.readiness == PreparedWindowReadiness::Ready)?;
+        let taken = slots.remove(index);
+        if slots.is_empty() {
+            self.prepared_windows.remove(&name);
+        }
 
-    if should_take {
-        let removed = inventory.remove(&name);
-        return removed;
+        Some(taken)
     }
 
-    None
-}
-
-#[cfg(test)]
-fn reset_prepared_window_inventory() {
-    let mut inventory = prepared_window_inventory()
-        .lock()
-        .expect("prepared window inventory poisoned");
-    inventory.clear();
+    fn release_prepared_window_for_label(&mut self, label: &str) -> Option<WindowName> {
+        let (name, slots) = self
+            .prepared_windows
+            .iter_mut()
+            .find(|(_, slots)| slots.iter().any(|state| state.label == label))?;
+        let name = *name;
+        slots.retain(|state| state.label != label);
+        if slots.is_empty() {
+            self.prepared_windows.remove(&name);
+        }
+        Some(name)
+    }
 
-    let mut warm_owners = warm_window_owners()
-        .lock()
-        .expect("warm window owners poisoned");
-    warm_owners.clear();
+    fn has_warm_window_owner(&self, name: WindowName) -> bool {
+        self.warm_window_owners
+            .get(&name)
+            .is_some_and(|labels| !labels.is_empty())
+    }
 
-    let mut promoted_labels = promoted_user_window_labels()
-        .lock()
-        .expect("promoted user window labels poisoned");
-    promoted_labels.clear();
-}
+    fn is_closing_for_graceful_shutdown(&self) -> bool {
+        matches!(
+            self.graceful_shutdown,
+            Some(GracefulShutdownProgress::Closing { .. })
+        )
+    }
 
-#[cfg(test)]
-fn prepared_window_targets() -> Vec<WindowName> {
-    let inventory = prepared_window_inventory()
-        .lock()
-        .expect("prepared window inventory poisoned");
-    let mut prepared = inventory.keys().copied().collect::<Vec<_>>();
-    prepared.sort_by_key(WindowName::as_str);
-    prepared
-}
+    /// A window destroyed while verdicts are collected can no longer veto, so
+    /// it stops being waited on.
+    fn mark_graceful_shutdown_window_destroyed(&mut self, label: &str) -> bool {
+        let pending_labels = match self.graceful_shutdown.as_mut() {
+            Some(GracefulShutdownProgress::AwaitingVerdicts {
+                awaiting_labels, ..
+            }) => {
+                awaiting_labels.remove(label);
+                return false;
+            }
+            Some(GracefulShutdownProgress::Closing { pending_labels }) => pending_labels,
+            None => return false,
+        };
 
-#[cfg(test)]
-fn prepared_window_label(name: WindowName) -> Option<String> {
-    let inventory = prepared_window_inventory()
-        .lock()
-        .expect("prepared window inventory poisoned");
-    inventory.get(&name).map(|state| state.label.clone())
-}
+        pending_labels.remove(label);
+        if !pending_labels.is_empty() {
+            return false;
+        }
 
-#[cfg(test)]
-fn prepared_window_readiness(name: WindowName) -> Option<PreparedWindowReadiness> {
-    let inventory = prepared_window_inventory()
-        .lock()
-        .expect("prepared window inventory poisoned");
-    inventory.get(&name).map(|state| state.readiness)
+        self.graceful_shutdown = None;
+        true
+    }
 }
 
-pub fn should_exit_on_window_close(app: &AppHandle, closing_label: &str) -> bool {
-    should_exit_on_window_close_after_visible_user_count(
-        closing_label,
-        visible_user_window_count(app),
-    )
+/// Lifecycle state for every window: prepared inventory, warm owners, promoted
+/// labels, launch payloads and shutdown progress. It is managed as Tauri state
+/// and everything sits behind one lock, so transitions that touch several of
+/// these parts are atomic.
+#[derive(Debug)]
+pub struct WindowRegistry {
+    state: Mutex<WindowRegistryState>,
+    shutdown_verdict_timeout: Duration,
 }
 
-fn release_prepared_window_for_label(label: &str) -> Option<WindowName> {
-    let mut inventory = prepared_window_inventory()
-        .lock()
-        .expect("prepared window inventory poisoned");
-    let name = inventory.iter().find_map(|(name, state)| {
-        if state.label == label {
-            Some(*name)
-        } else {
-            None
+impl Default for WindowRegistry {
+    fn default() -> Self {
+        Self {
+            state: Mutex::default(),
+            shutdown_verdict_timeout: DEFAULT_SHUTDOWN_VERDICT_TIMEOUT,
         }
-    })?;
-    inventory.remove(&name);
-    Some(name)
+    }
 }
 
-fn mark_graceful_shutdown_window_destroyed(label: &str) -> bool {
-    let mut shutdown = graceful_shutdown_state()
-        .lock()
-        .expect("graceful shutdown state poisoned");
-    let Some(progress) = shutdown.as_mut() else {
-        return false;
-    };
+impl WindowRegistry {
+    /// How long a shutdown request waits for user windows to answer. Windows
+    /// that stay silent are treated as agreeing, so a hung renderer cannot
+    /// keep the app from quitting. Apps use the default; tests shorten it.
+    #[cfg(test)]
+    fn shutdown_verdict_timeout(mut self, timeout: Duration) -> Self {
+        self.shutdown_verdict_timeout = timeout;
+        self
+    }
 
-    progress.pending_labels.remove(label);
-    if !progress.pending_labels.is_empty() {
-        return false;
+    fn lock(&self) -> MutexGuard<'_, WindowRegistryState> {
+        self.state.lock().expect("window registry poisoned")
     }
 
-    *shutdown = None;
-    true
-}
+    pub fn is_label_reserved_for_prepared_window(&self, label: &str) -> bool {
+        self.lock().is_label_reserved_for_prepared_window(label)
+    }
 
-fn ensure_active_warm_window_targets_prepared(app: &AppHandle) {
-    for name in active_warm_window_targets() {
-        prewarm_window(app.clone(), name);
+    #[cfg(test)]
+    fn classify_window_identity(&self, label: &str) -> WindowIdentity {
+        self.lock().classify_window_identity(label)
     }
-}
-
-fn handle_destroyed_window_state(label: &str) -> bool {
-    demote_window_label_from_user_window(label);
-    let _ = release_prepared_window_for_label(label);
-    mark_graceful_shutdown_window_destroyed(label)
-}
 
-pub fn handle_window_destroyed(app: &AppHandle, label: &str) {
-    let emptied_targets = remove_warm_window_owner_label(label);
-    if handle_destroyed_window_state(label) {
-        app.exit(0);
-        return;
+    fn window_kind_info_for_label(&self, label: &str) -> WindowKindInfo {
+        self.lock().window_kind_info_for_label(label)
     }
 
-    if !is_graceful_shutdown_in_progress() {
-        for name in emptied_targets {
-            let _ = discard_prewarm_window(app.clone(), name);
-        }
-
-        ensure_active_warm_window_targets_prepared(app);
+    #[cfg(test)]
+    fn classify_window_labels<'a>(
+        &self,
+        labels: impl IntoIterator<Item = &'a str>,
+    ) -> Vec<WindowKindInfo> {
+        let state = self.lock();
+        labels
+            .into_iter()
+            .map(|label| state.window_kind_info_for_label(label))
+            .collect()
     }
-}
 
-pub fn begin_graceful_shutdown(app: &AppHandle, closing_label: &str) {
-    let labels = app.webview_windows().keys().cloned().collect::<Vec<_>>();
-    if !start_graceful_shutdown_tracking(labels.clone()) {
-        return;
+    pub fn should_label_resolve_as_user_window(&self, label: &str) -> bool {
+        self.lock().classify_window_identity(label).is_user_window
     }
 
-    let labels_to_close = graceful_shutdown_target_labels(labels, closing_label);
+    /// Geometry is remembered by label. A prewarm label is handed to a new
+    /// window every time the pool refills, so windows promoted from the pool
+    /// neither save nor restore geometry.
+    pub fn should_remember_window_geometry(&self, label: &str) -> bool {
+        self.should_label_resolve_as_user_window(label)
+            && !window_descriptor_for_label(label)
+                .is_some_and(|descriptor| descriptor.is_prewarm_label(label))
+    }
 
-    if let Some(window) = app.get_webview_window(closing_label) {
-        let _ = window.close();
-    } else if handle_destroyed_window_state(closing_label) {
-        app.exit(0);
+    pub fn should_activate_window_on_app_ready(&self, label: &str) -> bool {
+        self.should_label_resolve_as_user_window(label)
     }
 
-    for label in labels_to_close {
-        if let Some(window) = app.get_webview_window(&label) {
-            let _ = window.close();
-        } else if handle_destroyed_window_state(&label) {
-            app.exit(0);
+    /// Prepared and owned windows, and every window once shutdown has started,
+    /// always close for real; the close policy only governs windows shown to
+    /// the user.
+    fn window_close_action_after_visible_user_count(
+        &self,
+        closing_label: &str,
+        visible_user_window_count_before_close: usize,
+    ) -> WindowCloseAction {
+        let state = self.lock();
+        if state.is_closing_for_graceful_shutdown()
+            || state.owned_windows.contains_key(closing_label)
+        {
+            return WindowCloseAction::Close;
         }
-    }
-}
 
-#[cfg(test)]
-fn should_exit_on_window_close_with_count(
-    closing_label: &str,
-    visible_user_window_count: usize,
-) -> bool {
-    should_exit_on_window_close_after_visible_user_count(closing_label, visible_user_window_count)
-}
+        let Some(descriptor) = window_descriptor_for_label(closing_label) else {
+            return WindowCloseAction::Close;
+        };
+        let identity = state.classify_window_identity(closing_label);
+        if identity.is_prepared_window || !(identity.is_user_window || identity.is_primary_window) {
+            return WindowCloseAction::Close;
+        }
 
-pub fn visible_user_window_count(app: &AppHandle) -> usize {
-    app.webview_windows()
-        .values()
-        .filter(|window| {
-            let label = window.label();
-            should_label_resolve_as_user_window(label) && window.is_visible().unwrap_or(false)
-        })
-        .count()
-}
+        window_close_action_for_policy(
+            descriptor.close_policy,
+            identity.is_user_window,
+            visible_user_window_count_before_close,
+        )
+    }
 
-#[derive(Serialize, Type)]
-pub struct MouseWindowInfo {
-    mouse_x: i32,
-    mouse_y: i32,
-    window_x: i32,
-    window_y: i32,
-    window_width: u32,
-    window_height: u32,
-    rel_x: i32,
-    rel_y: i32,
-    pixel_ratio: f64,
-}
+    /// True from the shutdown request until the app exits or a window vetoes.
+    fn is_graceful_shutdown_in_progress(&self) -> bool {
+        self.lock().graceful_shutdown.is_some()
+    }
 
-#[tauri::command]
-#[specta::specta]
-pub fn get_mouse_and_window_position(window: WebviewWindow) -> Result<MouseWindowInfo, String> {
-    // ① 鼠标位置
-    let cursor = window
-        .cursor_position()
-        .map_err(|e| format!("获取鼠标位置失败: {e:?}"))?;
+    fn is_closing_for_graceful_shutdown(&self) -> bool {
+        self.lock().is_closing_for_graceful_shutdown()
+    }
 
-    // ② 窗口左上角
-    let win_pos = window
-        .outer_position()
-        .map_err(|e| format!("获取窗口位置失败: {e:?}"))?;
+    #[cfg(test)]
+    fn try_begin_graceful_shutdown(&self) -> bool {
+        self.start_graceful_shutdown_tracking(std::iter::empty::<String>())
+    }
 
-    // ③ 窗口尺寸
-    let win_size = window
-        .outer_size()
-        .map_err(|e| format!("获取窗口尺寸失败: {e:?}"))?;
+    #[cfg(test)]
+    fn start_graceful_shutdown_tracking(&self, labels: impl IntoIterator<Item = String>) -> bool {
+        let Some(request_id) = self.try_request_graceful_shutdown(None, std::iter::empty()) else {
+            return false;
+        };
 
-    // ④ 缩放因子
-    let pixel_ratio = window
-        .scale_factor()
-        .map_err(|e| format!("获取缩放因子失败: {e:?}"))?;
+        self.finish_shutdown_request(request_id, labels).is_some()
+    }
 
-    // ⑤ 计算相对坐标
-    let rel_x = cursor.x as i32 - win_pos.x;
-    let rel_y = cursor.y as i32 - win_pos.y;
+    /// Opens the veto round of a shutdown. Returns its request id, or `None`
+    /// when a shutdown is already under way.
+    fn try_request_graceful_shutdown(
+        &self,
+        closing_label: Option<&str>,
+        awaiting_labels: impl IntoIterator<Item = String>,
+    ) -> Option<u32> {
+        let mut state = self.lock();
+        if state.graceful_shutdown.is_some() {
+            return None;
+        }
 
-    Ok(MouseWindowInfo {
-        mouse_x: cursor.x as i32,
-        mouse_y: cursor.y as i32,
-        window_x: win_pos.x,
-        window_y: win_pos.y,
-        window_width: win_size.width,
-        window_height: win_size.height,
-        rel_x,
-        rel_y,
-        pixel_ratio,
-    })
-}
+        state.last_shutdown_request_id += 1;
+        let request_id = state.last_shutdown_request_id;
+        state.graceful_shutdown = Some(GracefulShutdownProgress::AwaitingVerdicts {
+            request_id,
+            closing_label: closing_label.map(str::to_string),
+            awaiting_labels: awaiting_labels.into_iter().collect(),
+        });
+        Some(request_id)
+    }
 
-#[derive(Serialize, Deserialize, Type)]
-pub struct CreateWindowOptions {
-    width: Option<f64>,
-    height: Option<f64>,
-}
+    /// A veto rolls the shutdown back on the spot; answers for another request,
+    /// or from windows that were not asked, are ignored.
+    fn record_shutdown_verdict(
+        &self,
+        request_id: u32,
+        label: &str,
+        verdict: ShutdownVerdict,
+    ) -> ShutdownVerdictOutcome {
+        let mut state = self.lock();
+        let Some(GracefulShutdownProgress::AwaitingVerdicts {
+            request_id: current_request_id,
+            awaiting_labels,
+            ..
+        }) = state.graceful_shutdown.as_mut()
+        else {
+            return ShutdownVerdictOutcome::Ignored;
+        };
+        if *current_request_id != request_id || !awaiting_labels.remove(label) {
+            return ShutdownVerdictOutcome::Ignored;
+        }
 
-pub fn apply_window_setup(window: &WebviewWindow, is_main: bool) {
-    #[cfg(not(target_os = "macos"))]
-    let _ = is_main;
-    #[cfg(target_os = "windows")]
-    {
-        let _ = window.set_decorations(false);
+        let every_window_answered = awaiting_labels.is_empty();
+        match verdict {
+            ShutdownVerdict::Veto => {
+                state.graceful_shutdown = None;
+                ShutdownVerdictOutcome::Vetoed
+            }
+            ShutdownVerdict::Proceed if every_window_answered => ShutdownVerdictOutcome::Approved,
+            ShutdownVerdict::Proceed => ShutdownVerdictOutcome::Waiting,
+        }
     }
 
-    #[cfg(target_os = "macos")]
-    {
-        use super::macos_titlebar;
-        use objc2::MainThreadMarker;
-
-        macos_titlebar::setup_custom_macos_titlebar(window);
+    /// The pending request once every window it waited on has agreed or gone.
+    fn approved_shutdown_request(&self) -> Option<u32> {
+        match self.lock().graceful_shutdown.as_ref() {
+            Some(GracefulShutdownProgress::AwaitingVerdicts {
+                request_id,
+                awaiting_labels,
+                ..
+            }) if awaiting_labels.is_empty() => Some(*request_id),
+            _ => None,
+        }
+    }
 
-        if is_main {
-            if let Some(mtm) = MainThreadMarker::new() {
-                if let Some(observer) = macos_titlebar::FullscreenStateManager::new(window, mtm) {
-                    MAIN_WINDOW_OBSERVER.with(|cell| {
-                        let mut observer_ref = cell.borrow_mut();
-                        *observer_ref = Some(observer);
-                    });
-                } else {
-                    eprintln!("Failed to create FullscreenObserver.");
-                }
-            } else {
-                eprintln!("Failed to get MainThreadMarker for FullscreenObserver setup.");
-            }
+    /// Ends the veto round of `request_id` and starts waiting for `labels` to
+    /// be destroyed. Returns the label of the window that asked to close, or
+    /// `None` when the round already ended through a veto or another caller.
+    fn finish_shutdown_request(
+        &self,
+        request_id: u32,
+        labels: impl IntoIterator<Item = String>,
+    ) -> Option<Option<String>> {
+        let mut state = self.lock();
+        let Some(GracefulShutdownProgress::AwaitingVerdicts {
+            request_id: current_request_id,
+            closing_label,
+            ..
+        }) = state.graceful_shutdown.as_mut()
+        else {
+            return None;
+        };
+        if *current_request_id != request_id {
+            return None;
         }
 
-        window.on_window_event(|event| {
-            let _ = matches!(event, tauri::WindowEvent::Resized(_));
+        let closing_label = closing_label.take();
+        state.graceful_shutdown = Some(GracefulShutdownProgress::Closing {
+            pending_labels: labels.into_iter().collect(),
         });
+        Some(closing_label)
     }
-}
 
-pub fn configure_existing_primary_windows(app: &tauri::AppHandle) {
-    for descriptor in WINDOW_DESCRIPTORS {
-        if !descriptor.uses_primary_window_setup {
-            continue;
-        }
+    #[cfg(test)]
+    fn graceful_shutdown_awaiting_labels(&self) -> Vec<String> {
+        let state = self.lock();
+        let Some(GracefulShutdownProgress::AwaitingVerdicts {
+            awaiting_labels, ..
+        }) = state.graceful_shutdown.as_ref()
+        else {
+            return Vec::new();
+        };
+
+        let mut labels = awaiting_labels.iter().cloned().collect::<Vec<_>>();
+        labels.sort();
+        labels
+    }
 
-        if let Some(window) = app.get_webview_window(descriptor.base_label) {
-            apply_window_setup(&window, true);
-        }
+    #[cfg(test)]
+    fn graceful_shutdown_pending_labels(&self) -> Vec<String> {
+        let state = self.lock();
+        let Some(GracefulShutdownProgress::Closing { pending_labels }) =
+            state.graceful_shutdown.as_ref()
+        else {
+            return Vec::new();
+        };
+
+        let mut labels = pending_labels.iter().cloned().collect::<Vec<_>>();
+        labels.sort();
+        labels
     }
-}
 
-#[derive(Serialize, Deserialize, Type, Clone, Copy, Debug, PartialEq, Eq, Hash)]
-pub enum WindowName {
-    Main,
-    Support,
-}
+    fn add_warm_window_owner(&self, name: WindowName, label: &str) -> bool {
+        self.lock()
+            .warm_window_owners
+            .entry(name)
+            .or_default()
+            .insert(label.to_string())
+    }
 
-impl WindowName {
-    pub const fn as_str(&self) -> &'static str {
-        match self {
-            WindowName::Main => "main",
-            WindowName::Support => "support",
+    fn remove_warm_window_owner(&self, name: WindowName, label: &str) -> bool {
+        let mut state = self.lock();
+        let Some(labels) = state.warm_window_owners.get_mut(&name) else {
+            return false;
+        };
+
+        labels.remove(label);
+        if labels.is_empty() {
+            state.warm_window_owners.remove(&name);
+            return false;
         }
+
+        true
     }
-}
 
-impl fmt::Display for WindowName {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.write_str(self.as_str())
+    fn remove_warm_window_owner_label(&self, label: &str) -> Vec<WindowName> {
+        let mut state = self.lock();
+        let mut emptied_targets = Vec::new();
+
+        state.warm_window_owners.retain(|name, labels| {
+            labels.remove(label);
+            let keep = !labels.is_empty();
+            if !keep {
+                emptied_targets.push(*name);
+            }
+            keep
+        });
+
+        emptied_targets.sort_by_key(WindowName::as_str);
+        emptied_targets
     }
-}
 
-fn next_visible_label(name: WindowName, app: &tauri::AppHandle) -> String {
-    let descriptor = window_descriptor(name);
-    if app.get_webview_window(descriptor.base_label).is_none() {
-        return descriptor.base_label.to_string();
+    fn has_warm_window_owner(&self, name: WindowName) -> bool {
+        self.lock().has_warm_window_owner(name)
     }
 
-    for index in 1.. {
-        let label = format!("{}-{index}", descriptor.base_label);
-        if app.get_webview_window(&label).is_none() {
-            return label;
-        }
+    fn active_warm_window_targets(&self) -> Vec<WindowName> {
+        let state = self.lock();
+        let mut targets = state
+            .warm_window_owners
+            .iter()
+            .filter_map(|(name, labels)| (!labels.is_empty()).then_some(*name))
+            .collect::<Vec<_>>();
+        targets.sort_by_key(WindowName::as_str);
+        targets
     }
-    unreachable!("graph window label overflow")
-}
 
-fn next_prewarm_label(name: WindowName, app: &tauri::AppHandle) -> String {
-    let descriptor = window_descriptor(name);
-    let base_label = descriptor.canonical_prewarm_label();
-    if app.get_webview_window(&base_label).is_none() {
-        return base_label;
+    fn store_window_launch_payload(&self, label: &str, payload: Option<WindowLaunchPayload>) {
+        let mut state = self.lock();
+        match payload {
+            Some(payload) => {
+                state
+                    .session_payloads
+                    .insert(label.to_string(), payload.clone());
+                state.launch_payloads.insert(label.to_string(), payload);
+            }
+            None => {
+                state.session_payloads.remove(label);
+                state.launch_payloads.remove(label);
+            }
+        }
     }
 
-    for index in 1.. {
-        let label = format!("{}-{index}", descriptor.canonical_prewarm_label());
-        if app.get_webview_window(&label).is_none() {
-            return label;
+    fn store_session_payload(&self, label: &str, payload: Option<WindowLaunchPayload>) {
+        let mut state = self.lock();
+        match payload {
+            Some(payload) => {
+                state.session_payloads.insert(label.to_string(), payload);
+            }
+            None => {
+                state.session_payloads.remove(label);
+            }
         }
     }
 
-    unreachable!("prewarm window label overflow")
-}
+    pub fn session_payload_for_label(&self, label: &str) -> Option<WindowLaunchPayload> {
+        self.lock().session_payloads.get(label).cloned()
+    }
 
-fn build_window(
-    app: &tauri::AppHandle,
-    label: String,
-    descriptor: &WindowDescriptor,
-    visible: bool,
-) -> Result<WebviewWindow, String> {
-    let mut builder = WebviewWindowBuilder::new(app, label, WebviewUrl::App("index.html".into()))
-        .title(descriptor.title)
-        .visible(visible)
-        .focused(visible)
-        .accept_first_mouse(true)
-        .inner_size(descriptor.default_width, descriptor.default_height)
-        .min_inner_size(descriptor.min_width, descriptor.min_height);
+    fn take_launch_payload_for_label(&self, label: &str) -> Option<WindowLaunchPayload> {
+        let mut state = self.lock();
+        // A hidden prepared window has not been handed to anyone yet, so it must not
+        // consume a payload that is only attached once it is promoted.
+        if state.is_label_reserved_for_prepared_window(label) {
+            return None;
+        }
 
-    #[cfg(not(any(target_os = "macos", target_os = "ios")))]
-    {
-        if let Ok(app_local_data_dir) = app.path().app_local_data_dir() {
-            let webview_data_dir = app_local_data_dir.join("webview-profile");
-            let _ = std::fs::create_dir_all(&webview_data_dir);
-            builder = builder.data_directory(webview_data_dir);
+        state.launch_payloads.remove(label)
+    }
+
+    /// Returns whether the label just became prepared inventory.
+    fn reserve_prepared_window(&self, name: WindowName, label: String) -> bool {
+        let mut state = self.lock();
+        let slots = state.prepared_windows.entry(name).or_default();
+        match slots.iter_mut().find(|slot| slot.label == label) {
+            Some(slot) if slot.readiness == PreparedWindowReadiness::Scheduled => {
+                slot.transition(PreparedWindowReadiness::Created);
+                true
+            }
+            Some(_) => false,
+            None => {
+                slots.push(PreparedWindowState::new(
+                    label,
+                    PreparedWindowReadiness::Created,
+                ));
+                true
+            }
         }
     }
 
-    builder.build().map_err(|error| error.to_string())
-}
+    /// Claims labels for the missing pool slots up front so that concurrent refills
+    /// never build the same label twice or overshoot the pool and global caps.
+    fn schedule_prepared_windows(
+        &self,
+        name: WindowName,
+        pool_size: usize,
+        is_label_free: impl Fn(&str) -> bool,
+    ) -> Vec<String> {
+        let mut state = self.lock();
+        let total_slots = state.prepared_windows.values().map(Vec::len).sum::<usize>();
+        let slots_for_target = state.prepared_windows.get(&name).map_or(0, Vec::len);
+        let refill_count = prepared_window_refill_count(pool_size, slots_for_target, total_slots);
+        if refill_count == 0 {
+            return Vec::new();
+        }
 
-fn clamp_i64_to_i32(value: i64) -> i32 {
-    value.clamp(i64::from(i32::MIN), i64::from(i32::MAX)) as i32
-}
+        let reserved_labels = state
+            .prepared_windows
+            .values()
+            .flatten()
+            .map(|slot| slot.label.clone())
+            .collect::<HashSet<_>>();
+        let labels = prewarm_label_candidates(name)
+            .filter(|label| !reserved_labels.contains(label) && is_label_free(label.as_str()))
+            .take(refill_count)
+            .collect::<Vec<_>>();
 
-fn centered_axis_position(area_start: i32, area_size: u32, window_size: u32) -> i32 {
-    if window_size >= area_size {
-        return area_start;
+        for label in &labels {
+            state.promoted_user_window_labels.remove(label);
+        }
+        state
+            .prepared_windows
+            .entry(name)
+            .or_default()
+            .extend(labels.iter().map(|label| {
+                PreparedWindowState::new(label.clone(), PreparedWindowReadiness::Scheduled)
+            }));
+        labels
     }
 
-    let centered = i64::from(area_start) + i64::from((area_size - window_size) / 2);
-    clamp_i64_to_i32(centered)
-}
+    fn prune_missing_prepared_windows(
+        &self,
+        name: WindowName,
+        window_exists: impl Fn(&str) -> bool,
+    ) {
+        let mut state = self.lock();
+        let Some(slots) = state.prepared_windows.get_mut(&name) else {
+            return;
+        };
 
-fn centered_position_in_area(
-    area_position: PhysicalPosition<i32>,
-    area_size: PhysicalSize<u32>,
-    window_size: PhysicalSize<u32>,
-) -> PhysicalPosition<i32> {
-    PhysicalPosition::new(
-        centered_axis_position(area_position.x, area_size.width, window_size.width),
-        centered_axis_position(area_position.y, area_size.height, window_size.height),
-    )
-}
+        slots.retain(|slot| {
+            slot.readiness == PreparedWindowReadiness::Scheduled
+                || window_exists(slot.label.as_str())
+        });
+        if slots.is_empty() {
+            state.prepared_windows.remove(&name);
+        }
+    }
 
-fn is_window_fully_within_area(
-    window_position: PhysicalPosition<i32>,
-    window_size: PhysicalSize<u32>,
-    area_position: PhysicalPosition<i32>,
-    area_size: PhysicalSize<u32>,
-) -> bool {
-    let window_left = i64::from(window_position.x);
-    let window_top = i64::from(window_position.y);
-    let window_right = window_left + i64::from(window_size.width);
-    let window_bottom = window_top + i64::from(window_size.height);
+    /// A scheduled build is only wanted while the target still has a warm owner
+    /// and the label is still reserved; a discard in between drops the slot.
+    fn should_build_prepared_window(&self, name: WindowName, label: &str) -> bool {
+        let state = self.lock();
+        state.has_warm_window_owner(name) && state.is_label_reserved_for_prepared_window(label)
+    }
 
-    let area_left = i64::from(area_position.x);
-    let area_top = i64::from(area_position.y);
-    let area_right = area_left + i64::from(area_size.width);
-    let area_bottom = area_top + i64::from(area_size.height);
+    fn record_prewarm_pool_outcome(&self, name: WindowName, outcome: PrewarmPoolOutcome) {
+        let mut state = self.lock();
+        let entry = state
+            .prewarm_pool_metrics
+            .entry(name)
+            .or_insert(PrewarmPoolMetrics {
+                window: name,
+                pool_hits: 0,
+                cold_opens: 0,
+                recycled: 0,
+            });
+        let counter = match outcome {
+            PrewarmPoolOutcome::Hit => &mut entry.pool_hits,
+            PrewarmPoolOutcome::ColdOpen => &mut entry.cold_opens,
+            PrewarmPoolOutcome::Recycled => &mut entry.recycled,
+        };
+        *counter = counter.saturating_add(1);
+    }
 
-    window_left >= area_left
-        && window_top >= area_top
-        && window_right <= area_right
-        && window_bottom <= area_bottom
-}
+    fn prewarm_pool_metrics(&self) -> Vec<PrewarmPoolMetrics> {
+        let state = self.lock();
+        let mut metrics = state
+            .prewarm_pool_metrics
+            .values()
+            .copied()
+            .collect::<Vec<_>>();
+        metrics.sort_by_key(|entry| entry.window.as_str());
+        metrics
+    }
 
-fn ensure_window_visible_on_opener_monitor(window: &WebviewWindow, opener: &WebviewWindow) {
-    let Ok(Some(monitor)) = opener.current_monitor() else {
-        return;
-    };
+    fn snapshot(
+        &self,
+        windows: impl IntoIterator<Item = (String, bool)>,
+        now: Instant,
+    ) -> WindowRegistrySnapshot {
+        let state = self.lock();
+
+        let mut windows = windows
+            .into_iter()
+            .map(|(label, visible)| LiveWindowSnapshot {
+                kind: state.window_kind_info_for_label(&label),
+                visible,
+            })
+            .collect::<Vec<_>>();
+        windows.sort_by(|left, right| left.kind.label.cmp(&right.kind.label));
 
-    let work_area = monitor.work_area();
-    let area_position = work_area.position;
-    let area_size = work_area.size;
-    let Ok(window_size) = window.outer_size() else {
-        return;
-    };
+        let mut prepared_windows = state
+            .prepared_windows
+            .iter()
+            .flat_map(|(name, slots)| {
+                slots.iter().map(move |slot| PreparedWindowSnapshot {
+                    window: *name,
+                    label: slot.label.clone(),
+                    readiness: slot.readiness,
+                    readiness_age_ms: now
+                        .saturating_duration_since(slot.since)
+                        .as_millis()
+                        .min(u128::from(u32::MAX)) as u32,
+                })
+            })
+            .collect::<Vec<_>>();
+        prepared_windows.sort_by(|left, right| left.label.cmp(&right.label));
 
-    if let Ok(window_position) = window.outer_position()
-        && is_window_fully_within_area(window_position, window_size, area_position, area_size)
-    {
-        return;
+        let mut warm_window_owners = state
+            .warm_window_owners
+            .iter()
+            .map(|(name, labels)| {
+                let mut owners = labels.iter().cloned().collect::<Vec<_>>();
+                owners.sort();
+                WarmWindowOwnersSnapshot {
+                    window: *name,
+                    owners,
+                }
+            })
+            .collect::<Vec<_>>();
+        warm_window_owners.sort_by_key(|snapshot| snapshot.window.as_str());
+
+        let sorted_labels = |labels: &HashSet<String>| {
+            let mut labels = labels.iter().cloned().collect::<Vec<_>>();
+            labels.sort();
+            labels
+        };
+        let graceful_shutdown = state
+            .graceful_shutdown
+            .as_ref()
+            .map(|progress| match progress {
+                GracefulShutdownProgress::AwaitingVerdicts {
+                    awaiting_labels, ..
+                } => GracefulShutdownSnapshot {
+                    awaiting_verdict_labels: sorted_labels(awaiting_labels),
+                    pending_labels: Vec::new(),
+                },
+                GracefulShutdownProgress::Closing { pending_labels } => GracefulShutdownSnapshot {
+                    awaiting_verdict_labels: Vec::new(),
+                    pending_labels: sorted_labels(pending_labels),
+                },
+            });
+
+        WindowRegistrySnapshot {
+            windows,
+            prepared_windows,
+            warm_window_owners,
+            graceful_shutdown,
+        }
     }
 
-    let target_position = centered_position_in_area(area_position, area_size, window_size);
-    let _ = window.set_position(Position::Physical(target_position));
-}
-
-pub fn activate_window(window: &WebviewWindow) {
-    let label = window.label().to_string();
+    #[cfg(test)]
+    fn promote_window_label_to_user_window(&self, label: &str) -> bool {
+        self.lock()
+            .promoted_user_window_labels
+            .insert(label.to_string())
+    }
 
-    if let Err(error) = window.unminimize() {
-        eprintln!("Failed to unminimize window {label}: {error}");
+    fn demote_window_label_from_user_window(&self, label: &str) {
+        self.lock().promoted_user_window_labels.remove(label);
     }
-    if let Err(error) = window.show() {
-        eprintln!("Failed to show window {label}: {error}");
+
+    /// Hands out a ready prepared window and promotes it in the same critical
+    /// section, so no reader ever sees the label as neither prepared nor user.
+    fn take_prepared_window_and_promote(&self, name: WindowName) -> Option<String> {
+        let mut state = self.lock();
+        let taken = state.take_ready_prepared_window(name)?;
+        state
+            .promoted_user_window_labels
+            .insert(taken.label.clone());
+        Some(taken.label)
     }
-    if let Err(error) = window.set_focus() {
-        eprintln!("Failed to focus window {label}: {error}");
+
+    fn mark_prepared_window_ready(&self, name: WindowName, label: &str) -> bool {
+        let mut state = self.lock();
+        let Some(slot) = state
+            .prepared_windows
+            .get_mut(&name)
+            .and_then(|slots| slots.iter_mut().find(|slot| slot.label == label))
+        else {
+            return false;
+        };
+
+        slot.transition(PreparedWindowReadiness::Ready);
+        true
     }
-}
 
-pub fn should_activate_window_on_app_ready(label: &str) -> bool {
-    should_label_resolve_as_user_window(label)
+    fn stale_prepared_windows(
+        &self,
+        now: Instant,
+    ) -> Vec<(WindowName, String, PreparedWindowRecycleReason)> {
+        let state = self.lock();
+        let mut stale = state
+            .prepared_windows
+            .iter()
+            .flat_map(|(name, slots)| slots.iter().map(move |slot| (*name, slot)))
+            .filter_map(|(name, slot)| {
+                let age = now.saturating_duration_since(slot.since);
+                let reason = match slot.readiness {
+                    PreparedWindowReadiness::Scheduled | PreparedWindowReadiness::Created
+                        if age >= PREPARED_WINDOW_READY_TIMEOUT =>
+                    {
+                        PreparedWindowRecycleReason::ReadyTimeout
+                    }
+                    PreparedWindowReadiness::Ready if age >= PREPARED_WINDOW_TTL => {
+                        PreparedWindowRecycleReason::Expired
+                    }
+                    _ => return None,
+                };
+                Some((name, slot.label.clone(), reason))
+            })
+            .collect::<Vec<_>>();
+        stale.sort_by(|left, right| left.1.cmp(&right.1));
+        stale
+    }
+
+    fn ready_prepared_window_target(&self, label: &str) -> Option<WindowName> {
+        let state = self.lock();
+        state.prepared_windows.iter().find_map(|(name, slots)| {
+            slots
+                .iter()
+                .any(|slot| slot.label == label && slot.readiness == PreparedWindowReadiness::Ready)
+                .then_some(*name)
+        })
+    }
+
+    #[cfg(test)]
+    fn discard_prepared_window(&self, name: WindowName) -> bool {
+        self.lock().prepared_windows.remove(&name).is_some()
+    }
+
+    fn discard_prepared_window_state(&self, name: WindowName) -> PreparedWindowDisposition {
+        let mut state = self.lock();
+        let disposition = match state.prepared_windows.remove(&name) {
+            Some(slots) => PreparedWindowDisposition {
+                labels: slots.into_iter().map(|slot| slot.label).collect(),
+                removed_from_inventory: true,
+            },
+            None => PreparedWindowDisposition {
+                labels: vec![window_descriptor(name).canonical_prewarm_label()],
+                removed_from_inventory: false,
+            },
+        };
+
+        for label in &disposition.labels {
+            state.promoted_user_window_labels.remove(label);
+        }
+        disposition
+    }
+
+    #[cfg(test)]
+    fn take_prepared_window(&self, name: WindowName) -> Option<PreparedWindowState> {
+        self.lock().take_ready_prepared_window(name)
+    }
+
+    #[cfg(test)]
+    fn prepared_window_targets(&self) -> Vec<WindowName> {
+        let state = self.lock();
+        let mut prepared = state
+            .prepared_windows
+            .iter()
+            .filter_map(|(name, slots)| (!slots.is_empty()).then_some(*name))
+            .collect::<Vec<_>>();
+        prepared.sort_by_key(WindowName::as_str);
+        prepared
+    }
+
+    #[cfg(test)]
+    fn prepared_window_label(&self, name: WindowName) -> Option<String> {
+        self.lock()
+            .prepared_windows
+            .get(&name)
+            .and_then(|slots| slots.first())
+            .map(|slot| slot.label.clone())
+    }
+
+    #[cfg(test)]
+    fn prepared_window_readiness(&self, name: WindowName) -> Option<PreparedWindowReadiness> {
+        self.lock()
+            .prepared_windows
+            .get(&name)
+            .and_then(|slots| slots.first())
+            .map(|slot| slot.readiness)
+    }
+
+    fn release_prepared_window_for_label(&self, label: &str) -> Option<WindowName> {
+        self.lock().release_prepared_window_for_label(label)
+    }
+
+    fn attach_owned_window(&self, label: &str, owner_label: &str, modal: bool) {
+        self.lock().owned_windows.insert(
+            label.to_string(),
+            OwnedWindow {
+                owner_label: owner_label.to_string(),
+                modal,
+            },
+        );
+    }
+
+    fn release_owned_windows(&self, label: &str) -> OwnedWindowRelease {
+        self.lock().release_owned_windows(label)
+    }
+
+    /// A window has at most one position subscription; starting another one
+    /// ends the previous stream at its next tick.
+    fn start_position_subscription(&self, label: &str) -> u32 {
+        let mut state = self.lock();
+        state.last_position_subscription_id += 1;
+        let subscription_id = state.last_position_subscription_id;
+        state
+            .position_subscriptions
+            .insert(label.to_string(), subscription_id);
+        subscription_id
+    }
+
+    fn is_position_subscription_active(&self, label: &str, subscription_id: u32) -> bool {
+        self.lock().position_subscriptions.get(label) == Some(&subscription_id)
+    }
+
+    fn stop_position_subscription(&self, label: &str) -> bool {
+        self.lock().position_subscriptions.remove(label).is_some()
+    }
+
+    /// Returns whether `state` differs from what the window was last told.
+    fn record_window_state(&self, label: &str, state: WindowStateEvent) -> bool {
+        self.lock().window_states.insert(label.to_string(), state) != Some(state)
+    }
+
+    /// Clears everything tied to a destroyed label and returns whether it was
+    /// the last window graceful shutdown was waiting on.
+    fn handle_destroyed_window_state(&self, label: &str) -> bool {
+        let mut state = self.lock();
+        state.promoted_user_window_labels.remove(label);
+        state.launch_payloads.remove(label);
+        state.session_payloads.remove(label);
+        state.position_subscriptions.remove(label);
+        state.window_states.remove(label);
+        let _ = state.release_prepared_window_for_label(label);
+        state.mark_graceful_shutdown_window_destroyed(label)
+    }
 }
 
-fn apply_window_options(window: &WebviewWindow, options: Option<&CreateWindowOptions>) -> bool {
-    if let Some(options) = options
-        && let (Some(width), Some(height)) = (options.width, options.height)
+fn window_registry<R: Runtime>(app: &AppHandle<R>) -> State<'_, WindowRegistry> {
+    app.state::<WindowRegistry>()
+}
+
+fn emit_window_lifecycle_event<R: Runtime>(app: &AppHandle<R>, event: WindowLifecycleEvent) {
+    if let Err(error) = event.emit(app) {
+        eprintln!("Failed to emit window lifecycle event: {error}");
+    }
+}
+
+fn emit_window_visibility_changed<R: Runtime>(app: &AppHandle<R>, label: &str, visible: bool) {
+    emit_window_lifecycle_event(
+        app,
+        WindowLifecycleEvent::VisibilityChanged {
+            window: window_kind_from_label(label),
+            label: label.to_string(),
+            visible,
+        },
+    );
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn get_window_kind<R: Runtime>(
+    window: WebviewWindow<R>,
+    registry: State<'_, WindowRegistry>,
+) -> WindowKindInfo {
+    registry.window_kind_info_for_label(window.label())
+}
+
+fn window_close_action_for_policy(
+    policy: WindowClosePolicy,
+    is_user_window: bool,
+    visible_user_window_count_before_close: usize,
+) -> WindowCloseAction {
+    match policy {
+        WindowClosePolicy::HideWindow => WindowCloseAction::Hide,
+        WindowClosePolicy::KeepRunning => WindowCloseAction::Close,
+        WindowClosePolicy::ExitOnLastWindow
+            if is_user_window && visible_user_window_count_before_close.saturating_sub(1) == 0 =>
+        {
+            WindowCloseAction::Exit
+        }
+        WindowClosePolicy::ExitOnLastWindow => WindowCloseAction::Close,
+    }
+}
+
+fn graceful_shutdown_target_labels(
+    labels: impl IntoIterator<Item = String>,
+    closing_label: &str,
+) -> Vec<String> {
+    labels
+        .into_iter()
+        .filter(|label| label != closing_label)
+        .collect()
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn record_renderer_bootstrap_ready<R: Runtime>(
+    window: WebviewWindow<R>,
+    registry: State<'_, WindowRegistry>,
+) {
+    let label = window.label().to_string();
+    let Some(name) = window_kind_from_label(&label) else {
+        return;
+    };
+
+    if !registry.should_label_resolve_as_user_window(&label)
+        && registry.mark_prepared_window_ready(name, &label)
     {
-        let _ = window.set_size(Size::Logical(LogicalSize::new(width, height)));
-        return true;
+        emit_window_lifecycle_event(
+            window.app_handle(),
+            WindowLifecycleEvent::Ready {
+                window: name,
+                label,
+            },
+        );
     }
+}
 
-    false
+#[tauri::command]
+#[specta::specta]
+pub fn take_window_launch_payload<R: Runtime>(
+    window: WebviewWindow<R>,
+    registry: State<'_, WindowRegistry>,
+) -> Option<WindowLaunchPayload> {
+    registry.take_launch_payload_for_label(window.label())
+}
+
+/// Replaces the payload the calling window reopens with when the session is
+/// restored, so it comes back where the user left it.
+#[tauri::command]
+#[specta::specta]
+pub fn set_window_session_payload<R: Runtime>(
+    window: WebviewWindow<R>,
+    registry: State<'_, WindowRegistry>,
+    payload: Option<WindowLaunchPayload>,
+) {
+    registry.store_session_payload(window.label(), payload);
 }
 
+fn prepared_window_refill_count(
+    pool_size: usize,
+    slots_for_target: usize,
+    total_slots: usize,
+) -> usize {
+    pool_size
+        .saturating_sub(slots_for_target)
+        .min(MAX_PREPARED_WINDOWS.saturating_sub(total_slots))
+}
+
+fn prewarm_label_candidates(name: WindowName) -> impl Iterator<Item = String> {
+    let base_label = window_descriptor(name).canonical_prewarm_label();
+    std::iter::once(base_label.clone())
+        .chain((1..).map(move |index| format!("{base_label}-{index}")))
+}
+
+#[tauri::command]
 #[specta::specta]
+pub fn get_prewarm_pool_metrics(registry: State<'_, WindowRegistry>) -> Vec<PrewarmPoolMetrics> {
+    registry.prewarm_pool_metrics()
+}
+
 #[tauri::command]
-pub async fn create_window(
-    app: tauri::AppHandle,
-    window: WebviewWindow,
+#[specta::specta]
+pub fn get_window_registry_snapshot<R: Runtime>(
+    app: AppHandle<R>,
+    registry: State<'_, WindowRegistry>,
+) -> WindowRegistrySnapshot {
+    let windows = app
+        .webview_windows()
+        .into_iter()
+        .map(|(label, window)| (label, window.is_visible().unwrap_or(false)))
+        .collect::<Vec<_>>();
+    registry.snapshot(windows, Instant::now())
+}
+
+/// Drops the slot first so the closing webview can never be handed out, then
+/// closes it; the destroy handler refills the pool for active warm targets.
+fn recycle_prepared_window<R: Runtime>(
+    app: &AppHandle<R>,
     name: WindowName,
-    options: Option<CreateWindowOptions>,
+    label: &str,
+    reason: PreparedWindowRecycleReason,
 ) {
-    let descriptor = window_descriptor(name);
+    let registry = window_registry(app);
+    if registry.release_prepared_window_for_label(label).is_none() {
+        return;
+    }
+
+    registry.record_prewarm_pool_outcome(name, PrewarmPoolOutcome::Recycled);
+    eprintln!("Recycling prepared window {label} for {name}: {reason}");
+    match app.get_webview_window(label) {
+        Some(window) => {
+            let _ = window.close();
+        }
+        None => prewarm_window(app.clone(), name),
+    }
+}
+
+fn supervise_prepared_windows<R: Runtime>(app: &AppHandle<R>) {
+    let registry = window_registry(app);
+    if registry.is_graceful_shutdown_in_progress() {
+        return;
+    }
+
+    for (name, label, reason) in registry.stale_prepared_windows(Instant::now()) {
+        recycle_prepared_window(app, name, &label, reason);
+    }
+}
+
+pub fn start_prepared_window_supervisor<R: Runtime>(app: &AppHandle<R>) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(PREPARED_WINDOW_SUPERVISOR_INTERVAL).await;
+            supervise_prepared_windows(&app);
+        }
+    });
+}
+
+fn handle_prepared_window_page_load<R: Runtime>(
+    app: &AppHandle<R>,
+    label: &str,
+    event: PageLoadEvent,
+) {
+    if !matches!(event, PageLoadEvent::Started) {
+        return;
+    }
+
+    // The first load of a prepared window happens before it reports ready, so a
+    // fresh load on a ready slot means the renderer crashed or was reloaded.
+    if let Some(name) = window_registry(app).ready_prepared_window_target(label) {
+        recycle_prepared_window(
+            app,
+            name,
+            label,
+            PreparedWindowRecycleReason::RendererReloaded,
+        );
+    }
+}
+
+fn window_close_action<R: Runtime>(app: &AppHandle<R>, closing_label: &str) -> WindowCloseAction {
+    window_registry(app)
+        .window_close_action_after_visible_user_count(closing_label, visible_user_window_count(app))
+}
+
+/// Answers a `CloseRequested` event with the close policy of the window's
+/// kind. Returns whether the close has to be prevented.
+pub fn handle_close_requested<R: Runtime>(window: &Window<R>) -> bool {
+    let app = window.app_handle();
+    let label = window.label();
+    match window_close_action(app, label) {
+        WindowCloseAction::Close => false,
+        WindowCloseAction::Hide => {
+            match window.hide() {
+                Ok(()) => emit_window_visibility_changed(app, label, false),
+                Err(error) => eprintln!("Failed to hide window {label}: {error}"),
+            }
+            true
+        }
+        WindowCloseAction::Exit => {
+            begin_graceful_shutdown(app, Some(label));
+            true
+        }
+    }
+}
+
+/// Tauri asks to exit once the last window is gone; kinds that keep running
+/// veto that, unless the request comes from a graceful shutdown.
+pub fn should_keep_running_without_windows<R: Runtime>(app: &AppHandle<R>) -> bool {
+    !window_registry(app).is_closing_for_graceful_shutdown()
+        && keeps_running_without_windows(window_descriptor_registry())
+}
+
+fn keeps_running_without_windows(descriptors: &[WindowDescriptor]) -> bool {
+    descriptors
+        .iter()
+        .any(|descriptor| descriptor.close_policy == WindowClosePolicy::KeepRunning)
+}
+
+fn ensure_active_warm_window_targets_prepared<R: Runtime>(app: &AppHandle<R>) {
+    for name in window_registry(app).active_warm_window_targets() {
+        prewarm_window(app.clone(), name);
+    }
+}
+
+pub fn handle_window_destroyed<R: Runtime>(app: &AppHandle<R>, label: &str) {
+    emit_window_lifecycle_event(
+        app,
+        WindowLifecycleEvent::Destroyed {
+            window: window_kind_from_label(label),
+            label: label.to_string(),
+        },
+    );
+
+    release_owned_windows(app, label);
+
+    let registry = window_registry(app);
+    let emptied_targets = registry.remove_warm_window_owner_label(label);
+    if registry.handle_destroyed_window_state(label) {
+        shutdown::exit_after_shutdown_hooks(app);
+        return;
+    }
+
+    if let Some(request_id) = registry.approved_shutdown_request() {
+        close_windows_for_graceful_shutdown(app, request_id);
+        return;
+    }
+
+    if !registry.is_closing_for_graceful_shutdown() {
+        for name in emptied_targets {
+            let _ = discard_prewarm_window(app.clone(), name);
+        }
+
+        ensure_active_warm_window_targets_prepared(app);
+    }
+}
+
+/// Asks every user window whether the app may quit. Once all of them agree,
+/// go away, or the verdict timeout passes, every window is closed and the app
+/// exits; a single veto cancels the shutdown. Without a closing label the
+/// request comes from outside any window, such as the tray.
+pub fn begin_graceful_shutdown<R: Runtime>(app: &AppHandle<R>, closing_label: Option<&str>) {
+    let registry = window_registry(app);
+    let awaiting_labels = user_windows(app)
+        .iter()
+        .map(|window| window.label().to_string())
+        .collect::<Vec<_>>();
+    let Some(request_id) =
+        registry.try_request_graceful_shutdown(closing_label, awaiting_labels.clone())
+    else {
+        return;
+    };
+
+    if awaiting_labels.is_empty() {
+        close_windows_for_graceful_shutdown(app, request_id);
+        return;
+    }
+
+    emit_window_lifecycle_event(
+        app,
+        WindowLifecycleEvent::GracefulShutdownRequested {
+            request_id,
+            closing_label: closing_label.map(str::to_string),
+            awaiting_labels,
+        },
+    );
+
+    let timeout = registry.shutdown_verdict_timeout;
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(timeout).await;
+        close_windows_for_graceful_shutdown(&app, request_id);
+    });
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn answer_shutdown_request<R: Runtime>(
+    app: AppHandle<R>,
+    window: WebviewWindow<R>,
+    registry: State<'_, WindowRegistry>,
+    request_id: u32,
+    verdict: ShutdownVerdict,
+) {
+    let label = window.label().to_string();
+    match registry.record_shutdown_verdict(request_id, &label, verdict) {
+        ShutdownVerdictOutcome::Approved => close_windows_for_graceful_shutdown(&app, request_id),
+        ShutdownVerdictOutcome::Vetoed => emit_window_lifecycle_event(
+            &app,
+            WindowLifecycleEvent::GracefulShutdownCancelled {
+                request_id,
+                vetoed_by: label,
+            },
+        ),
+        ShutdownVerdictOutcome::Waiting | ShutdownVerdictOutcome::Ignored => {}
+    }
+}
+
+/// Second phase of a shutdown: closes every window and exits once the last
+/// one is destroyed. Does nothing if the request was vetoed or has already
+/// moved on, which makes the verdict timeout safe to fire late.
+fn close_windows_for_graceful_shutdown<R: Runtime>(app: &AppHandle<R>, request_id: u32) {
+    let registry = window_registry(app);
+    let labels = app.webview_windows().keys().cloned().collect::<Vec<_>>();
+    let Some(closing_label) = registry.finish_shutdown_request(request_id, labels.clone()) else {
+        return;
+    };
+    let closing_label = closing_label.as_deref();
+    session::freeze_session(app);
+
+    let mut pending_labels = labels.clone();
+    pending_labels.sort();
+    emit_window_lifecycle_event(
+        app,
+        WindowLifecycleEvent::GracefulShutdownStarted {
+            closing_label: closing_label.map(str::to_string),
+            pending_labels,
+        },
+    );
+
+    if labels.is_empty() {
+        shutdown::exit_after_shutdown_hooks(app);
+        return;
+    }
+
+    let labels_to_close =
+        graceful_shutdown_target_labels(labels, closing_label.unwrap_or_default());
+
+    if let Some(closing_label) = closing_label {
+        if let Some(window) = app.get_webview_window(closing_label) {
+            let _ = window.close();
+        } else if registry.handle_destroyed_window_state(closing_label) {
+            shutdown::exit_after_shutdown_hooks(app);
+        }
+    }
+
+    for label in labels_to_close {
+        if let Some(window) = app.get_webview_window(&label) {
+            let _ = window.close();
+        } else if registry.handle_destroyed_window_state(&label) {
+            shutdown::exit_after_shutdown_hooks(app);
+        }
+    }
+}
+
+/// Open user windows sorted by label, hidden ones included.
+pub fn user_windows<R: Runtime>(app: &AppHandle<R>) -> Vec<WebviewWindow<R>> {
+    let registry = window_registry(app);
+    let mut windows = app
+        .webview_windows()
+        .into_values()
+        .filter(|window| registry.should_label_resolve_as_user_window(window.label()))
+        .collect::<Vec<_>>();
+    windows.sort_by(|left, right| left.label().cmp(right.label()));
+    windows
+}
+
+pub fn visible_user_window_count<R: Runtime>(app: &AppHandle<R>) -> usize {
+    user_windows(app)
+        .iter()
+        .filter(|window| window.is_visible().unwrap_or(false))
+        .count()
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Type)]
+pub struct MouseWindowInfo {
+    mouse_x: i32,
+    mouse_y: i32,
+    window_x: i32,
+    window_y: i32,
+    window_width: u32,
+    window_height: u32,
+    rel_x: i32,
+    rel_y: i32,
+    pixel_ratio: f64,
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn get_mouse_and_window_position(window: WebviewWindow) -> Result<MouseWindowInfo, AppError> {
+    mouse_and_window_position(&window)
+}
+
+fn mouse_and_window_position<R: Runtime>(
+    window: &WebviewWindow<R>,
+) -> Result<MouseWindowInfo, AppError> {
+    // ① 鼠标位置
+    let cursor = window
+        .cursor_position()
+        .map_err(AppError::cursor_position_unavailable)?;
+
+    // ② 窗口左上角
+    let win_pos = window
+        .outer_position()
+        .map_err(AppError::window_geometry_unavailable)?;
+
+    // ③ 窗口尺寸
+    let win_size = window
+        .outer_size()
+        .map_err(AppError::window_geometry_unavailable)?;
+
+    // ④ 缩放因子
+    let pixel_ratio = window
+        .scale_factor()
+        .map_err(AppError::window_geometry_unavailable)?;
+
+    // ⑤ 计算相对坐标
+    let rel_x = cursor.x as i32 - win_pos.x;
+    let rel_y = cursor.y as i32 - win_pos.y;
+
+    Ok(MouseWindowInfo {
+        mouse_x: cursor.x as i32,
+        mouse_y: cursor.y as i32,
+        window_x: win_pos.x,
+        window_y: win_pos.y,
+        window_width: win_size.width,
+        window_height: win_size.height,
+        rel_x,
+        rel_y,
+        pixel_ratio,
+    })
+}
+
+fn position_update_interval(interval_ms: Option<u32>) -> Duration {
+    Duration::from_millis(u64::from(
+        interval_ms
+            .unwrap_or(DEFAULT_POSITION_UPDATE_INTERVAL_MS)
+            .clamp(
+                MIN_POSITION_UPDATE_INTERVAL_MS,
+                MAX_POSITION_UPDATE_INTERVAL_MS,
+            ),
+    ))
+}
+
+/// Samples the pointer and window every `interval` and sends a sample only
+/// when it differs from the last one sent. Ends once the subscription is
+/// replaced, cancelled or dropped with its window.
+async fn stream_position_updates<R: Runtime>(
+    window: WebviewWindow<R>,
+    channel: Channel<MouseWindowInfo>,
+    subscription_id: u32,
+    interval: Duration,
+) {
+    let mut ticker = tokio::time::interval(interval);
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+    let mut last_sent = None;
+    loop {
+        ticker.tick().await;
+        if !window_registry(window.app_handle())
+            .is_position_subscription_active(window.label(), subscription_id)
+        {
+            return;
+        }
+
+        let Ok(info) = mouse_and_window_position(&window) else {
+            continue;
+        };
+        if last_sent.as_ref() == Some(&info) {
+            continue;
+        }
+        if let Err(error) = channel.send(info.clone()) {
+            eprintln!(
+                "Stopping position updates for window {}: {error}",
+                window.label()
+            );
+            return;
+        }
+        last_sent = Some(info);
+    }
+}
+
+/// Pushes `MouseWindowInfo` for the calling window over `channel`, sampled
+/// every `interval_ms`, instead of having the renderer poll
+/// `get_mouse_and_window_position` every frame.
+#[tauri::command]
+#[specta::specta]
+pub fn subscribe_mouse_and_window_position<R: Runtime>(
+    window: WebviewWindow<R>,
+    registry: State<'_, WindowRegistry>,
+    channel: Channel<MouseWindowInfo>,
+    interval_ms: Option<u32>,
+) {
+    let subscription_id = registry.start_position_subscription(window.label());
+    tauri::async_runtime::spawn(stream_position_updates(
+        window,
+        channel,
+        subscription_id,
+        position_update_interval(interval_ms),
+    ));
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn unsubscribe_mouse_and_window_position<R: Runtime>(
+    window: WebviewWindow<R>,
+    registry: State<'_, WindowRegistry>,
+) -> bool {
+    registry.stop_position_subscription(window.label())
+}
+
+fn read_window_state<R: Runtime>(window: &WebviewWindow<R>) -> Result<WindowStateEvent, AppError> {
+    Ok(WindowStateEvent {
+        is_fullscreen: window
+            .is_fullscreen()
+            .map_err(AppError::window_geometry_unavailable)?,
+        is_maximized: window
+            .is_maximized()
+            .map_err(AppError::window_geometry_unavailable)?,
+        is_minimized: window
+            .is_minimized()
+            .map_err(AppError::window_geometry_unavailable)?,
+        is_focused: window
+            .is_focused()
+            .map_err(AppError::window_geometry_unavailable)?,
+    })
+}
+
+/// Sends `WindowStateEvent` to `window` if its flags changed. `focused` comes
+/// from a `Focused` window event, which can arrive before `is_focused` agrees.
+pub fn sync_window_state<R: Runtime>(window: &WebviewWindow<R>, focused: Option<bool>) {
+    let mut state = match read_window_state(window) {
+        Ok(state) => state,
+        Err(error) => {
+            eprintln!("Failed to read state of window {}: {error}", window.label());
+            return;
+        }
+    };
+    if let Some(focused) = focused {
+        state.is_focused = focused;
+    }
+    if !window_registry(window.app_handle()).record_window_state(window.label(), state) {
+        return;
+    }
+    if let Err(error) = state.emit_to(window.app_handle(), window.label()) {
+        eprintln!("Failed to emit window state to {}: {error}", window.label());
+    }
+}
+
+/// Initial value for `WindowStateEvent`; later changes are only sent when they
+/// differ from what this returned.
+#[tauri::command]
+#[specta::specta]
+pub fn get_window_state<R: Runtime>(
+    window: WebviewWindow<R>,
+    registry: State<'_, WindowRegistry>,
+) -> Result<WindowStateEvent, AppError> {
+    let state = read_window_state(&window)?;
+    registry.record_window_state(window.label(), state);
+    Ok(state)
+}
+
+/// Which monitor `create_window` places a window on.
+#[derive(Serialize, Deserialize, Type, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(tag = "kind", content = "value")]
+pub enum MonitorPlacement {
+    OpenerMonitor,
+    CursorMonitor,
+    PrimaryMonitor,
+    /// Index into the list returned by `get_monitors`.
+    MonitorIndex(u32),
+}
+
+#[derive(Serialize, Deserialize, Type)]
+pub struct CreateWindowOptions {
+    width: Option<f64>,
+    height: Option<f64>,
+    placement: Option<MonitorPlacement>,
+}
+
+/// Where the window shown by `create_window` came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Type)]
+pub enum OpenedWindowSource {
+    /// A ready window from the prewarm pool was promoted.
+    Prewarmed,
+    /// No prepared window was ready, so a new one was built.
+    ColdBuild,
+    /// The kind is a singleton and its open window was focused.
+    Existing,
+}
+
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct OpenedWindow {
+    pub kind: WindowKindInfo,
+    pub source: OpenedWindowSource,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Type)]
+pub struct MonitorArea {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl MonitorArea {
+    fn new(position: PhysicalPosition<i32>, size: PhysicalSize<u32>) -> Self {
+        Self {
+            x: position.x,
+            y: position.y,
+            width: size.width,
+            height: size.height,
+        }
+    }
+}
+
+/// A connected display in physical pixels. `index` is what
+/// `MonitorPlacement::MonitorIndex` expects.
+#[derive(Debug, Clone, PartialEq, Serialize, Type)]
+pub struct MonitorInfo {
+    pub index: u32,
+    pub name: Option<String>,
+    pub bounds: MonitorArea,
+    pub work_area: MonitorArea,
+    pub scale_factor: f64,
+    pub is_primary: bool,
+}
+
+fn is_same_monitor(left: &Monitor, right: &Monitor) -> bool {
+    left.name() == right.name() && left.position() == right.position()
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn get_monitors<R: Runtime>(window: WebviewWindow<R>) -> Result<Vec<MonitorInfo>, AppError> {
+    let monitors = window
+        .available_monitors()
+        .map_err(AppError::monitors_unavailable)?;
+    let primary = window.primary_monitor().ok().flatten();
+
+    Ok(monitors
+        .iter()
+        .enumerate()
+        .map(|(index, monitor)| MonitorInfo {
+            index: index as u32,
+            name: monitor.name().cloned(),
+            bounds: MonitorArea::new(*monitor.position(), *monitor.size()),
+            work_area: MonitorArea::new(monitor.work_area().position, monitor.work_area().size),
+            scale_factor: monitor.scale_factor(),
+            is_primary: primary
+                .as_ref()
+                .is_some_and(|primary| is_same_monitor(primary, monitor)),
+        })
+        .collect())
+}
+
+pub fn apply_window_setup<R: Runtime>(window: &WebviewWindow<R>, is_main: bool) {
+    #[cfg(not(target_os = "macos"))]
+    let _ = is_main;
+    #[cfg(target_os = "windows")]
+    {
+        let _ = window.set_decorations(false);
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        use super::macos_titlebar;
+        use objc2::MainThreadMarker;
+
+        macos_titlebar::setup_custom_macos_titlebar(window);
+
+        if is_main {
+            if let Some(mtm) = MainThreadMarker::new() {
+                if let Some(observer) = macos_titlebar::FullscreenStateManager::new(window, mtm) {
+                    MAIN_WINDOW_OBSERVER.with(|cell| {
+                        let mut observer_ref = cell.borrow_mut();
+                        *observer_ref = Some(observer);
+                    });
+                } else {
+                    eprintln!("Failed to create FullscreenObserver.");
+                }
+            } else {
+                eprintln!("Failed to get MainThreadMarker for FullscreenObserver setup.");
+            }
+        }
+
+        window.on_window_event(|event| {
+            let _ = matches!(event, tauri::WindowEvent::Resized(_));
+        });
+    }
+}
+
+pub fn configure_existing_primary_windows<R: Runtime>(app: &AppHandle<R>) {
+    for descriptor in window_descriptor_registry() {
+        if !descriptor.uses_primary_window_setup {
+            continue;
+        }
+
+        if let Some(window) = app.get_webview_window(&descriptor.base_label) {
+            let _ = window_state::restore_window_state(app, &window);
+            apply_window_setup(&window, true);
+        }
+    }
+}
+
+fn next_visible_label<R: Runtime>(name: WindowName, app: &AppHandle<R>) -> String {
+    let descriptor = window_descriptor(name);
+    if app.get_webview_window(&descriptor.base_label).is_none() {
+        return descriptor.base_label.clone();
+    }
+
+    for index in 1.. {
+        let label = format!("{}-{index}", descriptor.base_label);
+        if app.get_webview_window(&label).is_none() {
+            return label;
+        }
+    }
+    unreachable!("graph window label overflow")
+}
+
+fn build_window<R: Runtime>(
+    app: &AppHandle<R>,
+    label: String,
+    descriptor: &WindowDescriptor,
+    visible: bool,
+    owner: Option<&WebviewWindow<R>>,
+) -> Result<WebviewWindow<R>, AppError> {
+    let url = WebviewUrl::App(descriptor.route.clone().into());
+    let mut builder = WebviewWindowBuilder::new(app, label, url)
+        .title(&descriptor.title)
+        .visible(visible)
+        .focused(visible)
+        .accept_first_mouse(true)
+        .inner_size(descriptor.default_width, descriptor.default_height)
+        .min_inner_size(descriptor.min_width, descriptor.min_height);
+
+    if let Some(owner) = owner {
+        builder = builder
+            .parent(owner)
+            .map_err(AppError::window_build_failed)?;
+    }
+
+    if !visible {
+        builder = builder.on_page_load(|window, payload| {
+            handle_prepared_window_page_load(window.app_handle(), window.label(), payload.event());
+        });
+    }
+
+    #[cfg(not(any(target_os = "macos", target_os = "ios")))]
+    {
+        if let Ok(app_local_data_dir) = app.path().app_local_data_dir() {
+            let webview_data_dir = app_local_data_dir.join("webview-profile");
+            let _ = std::fs::create_dir_all(&webview_data_dir);
+            builder = builder.data_directory(webview_data_dir);
+        }
+    }
+
+    let window = builder.build().map_err(AppError::window_build_failed)?;
+    emit_window_lifecycle_event(
+        app,
+        WindowLifecycleEvent::Created {
+            window: Some(descriptor.name),
+            label: window.label().to_string(),
+            visible,
+        },
+    );
+    Ok(window)
+}
+
+fn attach_owned_window<R: Runtime>(
+    app: &AppHandle<R>,
+    window: &WebviewWindow<R>,
+    owner: &WebviewWindow<R>,
+    modal: bool,
+) {
+    window_registry(app).attach_owned_window(window.label(), owner.label(), modal);
+    if modal && let Err(error) = owner.set_enabled(false) {
+        eprintln!("Failed to block input to window {}: {error}", owner.label());
+    }
+}
+
+/// Owned windows are destroyed rather than closed, so a hide policy or a
+/// veto in the renderer cannot leave them behind without their owner.
+fn release_owned_windows<R: Runtime>(app: &AppHandle<R>, label: &str) {
+    let release = window_registry(app).release_owned_windows(label);
+    if let Some(owner) = release
+        .unblocked_owner_label
+        .and_then(|owner_label| app.get_webview_window(&owner_label))
+    {
+        if let Err(error) = owner.set_enabled(true) {
+            eprintln!(
+                "Failed to unblock input to window {}: {error}",
+                owner.label()
+            );
+        }
+        let _ = owner.set_focus();
+    }
+
+    for owned_label in release.owned_labels {
+        if let Some(window) = app.get_webview_window(&owned_label) {
+            let _ = window.destroy();
+        }
+    }
+}
+
+pub(crate) fn clamp_i64_to_i32(value: i64) -> i32 {
+    value.clamp(i64::from(i32::MIN), i64::from(i32::MAX)) as i32
+}
+
+pub(crate) fn clamp_i64_to_u32(value: i64) -> u32 {
+    value.clamp(0, i64::from(u32::MAX)) as u32
+}
+
+fn centered_axis_position(area_start: i32, area_size: u32, window_size: u32) -> i32 {
+    if window_size >= area_size {
+        return area_start;
+    }
+
+    let centered = i64::from(area_start) + i64::from((area_size - window_size) / 2);
+    clamp_i64_to_i32(centered)
+}
+
+fn centered_position_in_area(
+    area_position: PhysicalPosition<i32>,
+    area_size: PhysicalSize<u32>,
+    window_size: PhysicalSize<u32>,
+) -> PhysicalPosition<i32> {
+    PhysicalPosition::new(
+        centered_axis_position(area_position.x, area_size.width, window_size.width),
+        centered_axis_position(area_position.y, area_size.height, window_size.height),
+    )
+}
+
+pub fn is_window_fully_within_area(
+    window_position: PhysicalPosition<i32>,
+    window_size: PhysicalSize<u32>,
+    area_position: PhysicalPosition<i32>,
+    area_size: PhysicalSize<u32>,
+) -> bool {
+    let window_left = i64::from(window_position.x);
+    let window_top = i64::from(window_position.y);
+    let window_right = window_left + i64::from(window_size.width);
+    let window_bottom = window_top + i64::from(window_size.height);
+
+    let area_left = i64::from(area_position.x);
+    let area_top = i64::from(area_position.y);
+    let area_right = area_left + i64::from(area_size.width);
+    let area_bottom = area_top + i64::from(area_size.height);
+
+    window_left >= area_left
+        && window_top >= area_top
+        && window_right <= area_right
+        && window_bottom <= area_bottom
+}
+
+/// The next cascade slot after the opener. A window that would leave the work
+/// area wraps to its origin, or is centered when it is too large for it.
+fn cascaded_position_in_area(
+    opener_position: PhysicalPosition<i32>,
+    step: i32,
+    window_size: PhysicalSize<u32>,
+    area_position: PhysicalPosition<i32>,
+    area_size: PhysicalSize<u32>,
+) -> PhysicalPosition<i32> {
+    let next = PhysicalPosition::new(
+        opener_position.x.saturating_add(step),
+        opener_position.y.saturating_add(step),
+    );
+    if is_window_fully_within_area(next, window_size, area_position, area_size) {
+        return next;
+    }
+
+    if is_window_fully_within_area(area_position, window_size, area_position, area_size) {
+        return area_position;
+    }
+
+    centered_position_in_area(area_position, area_size, window_size)
+}
+
+fn cascade_window_from_opener<R: Runtime>(
+    window: &WebviewWindow<R>,
+    opener: Option<&WebviewWindow<R>>,
+    step: u32,
+) {
+    let Some(opener) = opener else {
+        return;
+    };
+    let (Ok(Some(monitor)), Ok(opener_position), Ok(window_size)) = (
+        opener.current_monitor(),
+        opener.outer_position(),
+        window.outer_size(),
+    ) else {
+        return;
+    };
+
+    let step = clamp_i64_to_i32((f64::from(step) * monitor.scale_factor()).round() as i64);
+    let work_area = monitor.work_area();
+    let target_position = cascaded_position_in_area(
+        opener_position,
+        step,
+        window_size,
+        work_area.position,
+        work_area.size,
+    );
+    let _ = window.set_position(Position::Physical(target_position));
+}
+
+fn place_window_near_opener<R: Runtime>(
+    window: &WebviewWindow<R>,
+    opener: Option<&WebviewWindow<R>>,
+    placement: WindowPlacement,
+) {
+    match placement {
+        WindowPlacement::OpenerMonitor => ensure_window_visible_on_opener_monitor(window, opener),
+        WindowPlacement::Cascade { step } => cascade_window_from_opener(window, opener, step),
+    }
+}
+
+fn ensure_window_visible_on_opener_monitor<R: Runtime>(
+    window: &WebviewWindow<R>,
+    opener: Option<&WebviewWindow<R>>,
+) {
+    if let Some(Ok(Some(monitor))) = opener.map(WebviewWindow::current_monitor) {
+        ensure_window_visible_on_monitor(window, &monitor);
+    }
+}
+
+/// Monitors are looked up through the window being placed, which sees the
+/// same displays as the app.
+fn placement_monitor<R: Runtime>(
+    window: &WebviewWindow<R>,
+    opener: Option<&WebviewWindow<R>>,
+    placement: MonitorPlacement,
+) -> Option<Monitor> {
+    let monitor = match placement {
+        MonitorPlacement::OpenerMonitor => opener?.current_monitor(),
+        MonitorPlacement::CursorMonitor => window
+            .cursor_position()
+            .and_then(|cursor| window.monitor_from_point(cursor.x, cursor.y)),
+        MonitorPlacement::PrimaryMonitor => window.primary_monitor(),
+        MonitorPlacement::MonitorIndex(index) => window
+            .available_monitors()
+            .map(|monitors| monitors.into_iter().nth(index as usize)),
+    };
+    monitor.ok().flatten()
+}
+
+/// A placement on the opener's monitor follows the descriptor, so cascades
+/// keep working; any other monitor gets the window centered on it.
+fn place_window<R: Runtime>(
+    window: &WebviewWindow<R>,
+    opener: Option<&WebviewWindow<R>>,
+    descriptor_placement: WindowPlacement,
+    requested_placement: Option<MonitorPlacement>,
+) {
+    match requested_placement.unwrap_or(MonitorPlacement::OpenerMonitor) {
+        MonitorPlacement::OpenerMonitor => {
+            place_window_near_opener(window, opener, descriptor_placement);
+        }
+        placement => {
+            if let Some(monitor) = placement_monitor(window, opener, placement) {
+                ensure_window_visible_on_monitor(window, &monitor);
+            }
+        }
+    }
+}
+
+/// Saved geometry wins over the defaults, but not over a monitor the caller
+/// asked for explicitly.
+fn should_place_window(
+    restore: WindowStateRestore,
+    did_resize: bool,
+    descriptor_placement: WindowPlacement,
+    requested_placement: Option<MonitorPlacement>,
+) -> bool {
+    let requests_other_monitor =
+        requested_placement.is_some_and(|placement| placement != MonitorPlacement::OpenerMonitor);
+    match restore {
+        WindowStateRestore::Restored => requests_other_monitor,
+        WindowStateRestore::MonitorMissing => true,
+        WindowStateRestore::NotSaved => {
+            // The default position only needs fixing after a resize, but a
+            // cascade always moves the window off its opener.
+            did_resize
+                || requests_other_monitor
+                || descriptor_placement != WindowPlacement::OpenerMonitor
+        }
+    }
+}
+
+fn ensure_window_visible_on_monitor<R: Runtime>(window: &WebviewWindow<R>, monitor: &Monitor) {
+    let work_area = monitor.work_area();
+    let area_position = work_area.position;
+    let area_size = work_area.size;
+    let Ok(window_size) = window.outer_size() else {
+        return;
+    };
+
+    if let Ok(window_position) = window.outer_position()
+        && is_window_fully_within_area(window_position, window_size, area_position, area_size)
+    {
+        return;
+    }
+
+    let target_position = centered_position_in_area(area_position, area_size, window_size);
+    let _ = window.set_position(Position::Physical(target_position));
+}
+
+pub fn activate_window<R: Runtime>(window: &WebviewWindow<R>) {
+    let label = window.label().to_string();
+
+    if let Err(error) = window.unminimize() {
+        eprintln!("Failed to unminimize window {label}: {error}");
+    }
+    let was_visible = window.is_visible().unwrap_or(true);
+    match window.show() {
+        Ok(()) if !was_visible => emit_window_visibility_changed(window.app_handle(), &label, true),
+        Ok(()) => {}
+        Err(error) => eprintln!("Failed to show window {label}: {error}"),
+    }
+    if let Err(error) = window.set_focus() {
+        eprintln!("Failed to focus window {label}: {error}");
+    }
+}
+
+/// Runs after the saved geometry is restored, so a size the caller asked for
+/// wins over the remembered one.
+fn apply_window_options<R: Runtime>(
+    window: &WebviewWindow<R>,
+    options: Option<&CreateWindowOptions>,
+) -> bool {
+    if let Some(options) = options
+        && let (Some(width), Some(height)) = (options.width, options.height)
+    {
+        let _ = window.set_size(Size::Logical(LogicalSize::new(width, height)));
+        return true;
+    }
+
+    false
+}
+
+#[specta::specta]
+#[tauri::command]
+pub async fn create_window<R: Runtime>(
+    app: AppHandle<R>,
+    window: WebviewWindow<R>,
+    name: WindowName,
+    options: Option<CreateWindowOptions>,
+    payload: Option<WindowLaunchPayload>,
+) -> Result<OpenedWindow, AppError> {
+    open_window(&app, Some(&window), name, options, payload)
+}
+
+/// Opens a window of the given kind, promoting a ready prepared window when
+/// one exists. A singleton kind that is already open is focused instead.
+/// Without an opener, for example from the tray, the window keeps its default
+/// placement and an owned kind opens without an owner.
+pub fn open_window<R: Runtime>(
+    app: &AppHandle<R>,
+    opener: Option<&WebviewWindow<R>>,
+    name: WindowName,
+    options: Option<CreateWindowOptions>,
+    payload: Option<WindowLaunchPayload>,
+) -> Result<OpenedWindow, AppError> {
+    let Some(descriptor) = find_window_descriptor(name) else {
+        return Err(AppError::window_descriptor_missing(name));
+    };
+    let registry = window_registry(app);
+    let requested_placement = options.as_ref().and_then(|options| options.placement);
+
+    if descriptor.instance_policy == WindowInstancePolicy::Singleton
+        && let Some(existing) = singleton_window(app, name)
+    {
+        retarget_singleton_window(app, &existing, payload);
+        return Ok(OpenedWindow {
+            kind: registry.window_kind_info_for_label(existing.label()),
+            source: OpenedWindowSource::Existing,
+        });
+    }
+
+    if let Some(prepared_label) = registry.take_prepared_window_and_promote(name) {
+        if let Some(window_to_show) = app.get_webview_window(&prepared_label) {
+            registry.record_prewarm_pool_outcome(name, PrewarmPoolOutcome::Hit);
+            emit_window_lifecycle_event(
+                app,
+                WindowLifecycleEvent::Promoted {
+                    window: Some(name),
+                    label: prepared_label.clone(),
+                },
+            );
+            registry.store_window_launch_payload(&prepared_label, payload);
+            let restore = window_state::restore_window_state(app, &window_to_show);
+            let did_resize = apply_window_options(&window_to_show, options.as_ref());
+            // A prepared window sits wherever it was built, so it is always
+            // placed unless its saved geometry was restored.
+            if restore != WindowStateRestore::Restored
+                || should_place_window(
+                    restore,
+                    did_resize,
+                    descriptor.placement,
+                    requested_placement,
+                )
+            {
+                place_window(
+                    &window_to_show,
+                    opener,
+                    descriptor.placement,
+                    requested_placement,
+                );
+            }
+            activate_window(&window_to_show);
+            if registry.has_warm_window_owner(name) {
+                prewarm_window(app.clone(), name);
+            }
+            let next_window_kind = registry.window_kind_info_for_label(&prepared_label);
+            let _ = window_to_show.emit(WINDOW_KIND_CHANGED_EVENT, next_window_kind.clone());
+            return Ok(OpenedWindow {
+                kind: next_window_kind,
+                source: OpenedWindowSource::Prewarmed,
+            });
+        }
+
+        registry.demote_window_label_from_user_window(&prepared_label);
+    }
+
+    registry.record_prewarm_pool_outcome(name, PrewarmPoolOutcome::ColdOpen);
+    let label = next_visible_label(name, app);
+    registry.store_window_launch_payload(&label, payload);
+    let owner = opener.filter(|_| descriptor.owned_by_opener);
+    match build_window(app, label.clone(), descriptor, true, owner) {
+        Ok(window_to_show) => {
+            if let Some(owner) = owner {
+                attach_owned_window(app, &window_to_show, owner, descriptor.modal);
+            }
+            let restore = window_state::restore_window_state(app, &window_to_show);
+            let did_resize = apply_window_options(&window_to_show, options.as_ref());
+            if should_place_window(
+                restore,
+                did_resize,
+                descriptor.placement,
+                requested_placement,
+            ) {
+                place_window(
+                    &window_to_show,
+                    opener,
+                    descriptor.placement,
+                    requested_placement,
+                );
+            }
+            apply_window_setup(
+                &window_to_show,
+                descriptor.uses_primary_window_setup
+                    && descriptor.is_primary_label(window_to_show.label()),
+            );
+            activate_window(&window_to_show);
+            prewarm_window(app.clone(), name);
+            Ok(OpenedWindow {
+                kind: registry.window_kind_info_for_label(&label),
+                source: OpenedWindowSource::ColdBuild,
+            })
+        }
+        Err(error) => {
+            registry.store_window_launch_payload(&label, None);
+            eprintln!("Failed to create window {name}: {error}");
+            Err(error)
+        }
+    }
+}
+
+/// Hands a payload to a window whose renderer has not bootstrapped yet, such
+/// as the primary window created from the app config at startup.
+pub fn set_window_launch_payload<R: Runtime>(
+    app: &AppHandle<R>,
+    label: &str,
+    payload: Option<WindowLaunchPayload>,
+) {
+    window_registry(app).store_window_launch_payload(label, payload);
+}
+
+pub fn primary_window<R: Runtime>(
+    app: &AppHandle<R>,
+    name: WindowName,
+) -> Option<WebviewWindow<R>> {
+    app.get_webview_window(&window_descriptor(name).base_label)
+}
+
+/// The open window of a singleton kind, hidden ones included. Prepared
+/// windows are skipped, since they have not been shown yet.
+fn singleton_window<R: Runtime>(app: &AppHandle<R>, name: WindowName) -> Option<WebviewWindow<R>> {
+    let registry = window_registry(app);
+    let mut windows = app
+        .webview_windows()
+        .into_values()
+        .filter(|window| {
+            window_kind_from_label(window.label()) == Some(name)
+                && !registry.is_label_reserved_for_prepared_window(window.label())
+        })
+        .collect::<Vec<_>>();
+    windows.sort_by(|left, right| left.label().cmp(right.label()));
+    windows.into_iter().next()
+}
+
+/// The renderer of an open window has already taken its launch payload, so a
+/// new one reaches it over the window bus.
+fn retarget_singleton_window<R: Runtime>(
+    app: &AppHandle<R>,
+    window: &WebviewWindow<R>,
+    payload: Option<WindowLaunchPayload>,
+) {
+    activate_window(window);
+    if let Some(payload) = payload
+        && let Err(error) = window_bus::send_launch_payload(app, window.label(), payload)
+    {
+        eprintln!(
+            "Failed to deliver launch payload to {}: {error}",
+            window.label()
+        );
+    }
+}
+
+#[specta::specta]
+#[tauri::command]
+pub fn warm_window<R: Runtime>(
+    app: AppHandle<R>,
+    window: WebviewWindow<R>,
+    registry: State<'_, WindowRegistry>,
+    name: WindowName,
+) {
+    let label = window.label().to_string();
+    if !registry.should_label_resolve_as_user_window(&label) {
+        return;
+    }
+
+    let _ = registry.add_warm_window_owner(name, &label);
+    prewarm_window(app, name);
+}
+
+#[specta::specta]
+#[tauri::command]
+pub fn cold_window<R: Runtime>(
+    app: AppHandle<R>,
+    window: WebviewWindow<R>,
+    registry: State<'_, WindowRegistry>,
+    name: WindowName,
+) -> bool {
+    let label = window.label().to_string();
+    if registry.remove_warm_window_owner(name, &label) {
+        return false;
+    }
+
+    discard_prewarm_window(app, name)
+}
+
+#[specta::specta]
+#[tauri::command]
+pub fn prewarm_window<R: Runtime>(app: AppHandle<R>, name: WindowName) {
+    let descriptor = window_descriptor(name);
+    let registry = window_registry(&app);
+    if !descriptor.prewarm_enabled || !registry.has_warm_window_owner(name) {
+        return;
+    }
+
+    registry.prune_missing_prepared_windows(name, |label| app.get_webview_window(label).is_some());
+    let labels = registry.schedule_prepared_windows(name, descriptor.prewarm_pool_size, |label| {
+        app.get_webview_window(label).is_none()
+    });
+
+    for label in labels {
+        schedule_prepared_window_build(app.clone(), name, label);
+    }
+}
+
+fn schedule_prepared_window_build<R: Runtime>(app: AppHandle<R>, name: WindowName, label: String) {
+    let descriptor = window_descriptor(name);
+    let scheduled_label_for_task = label.clone();
+    let app_handle = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let run_result = app_handle.run_on_main_thread(move || {
+            // A discard between scheduling and building drops the slot; the
+            // label must not come back as prepared inventory.
+            let registry = window_registry(&app);
+            if !registry.should_build_prepared_window(name, &label) {
+                let _ = registry.release_prepared_window_for_label(&label);
+                return;
+            }
+
+            match build_window(&app, label.clone(), descriptor, false, None) {
+                Ok(window) => {
+                    if !registry.should_build_prepared_window(name, &label) {
+                        let _ = registry.release_prepared_window_for_label(&label);
+                        let _ = window.close();
+                        return;
+                    }
+
+                    apply_window_setup(&window, false);
+                    if registry.reserve_prepared_window(name, label.clone()) {
+                        emit_window_lifecycle_event(
+                            &app,
+                            WindowLifecycleEvent::Prewarmed {
+                                window: name,
+                                label,
+                            },
+                        );
+                    }
+                }
+                Err(error) => {
+                    let _ = registry.release_prepared_window_for_label(&label);
+                    eprintln!("Failed to prewarm window: {error}");
+                }
+            }
+        });
+
+        if let Err(error) = run_result {
+            let _ = window_registry(&app_handle)
+                .release_prepared_window_for_label(&scheduled_label_for_task);
+            eprintln!(
+                "Failed to schedule prewarm window {scheduled_label_for_task} for {name}: {error}"
+            );
+        }
+    });
+}
+
+#[specta::specta]
+#[tauri::command]
+pub fn discard_prewarm_window<R: Runtime>(app: AppHandle<R>, name: WindowName) -> bool {
+    let disposition = window_registry(&app).discard_prepared_window_state(name);
+
+    let mut closed_window = false;
+    for label in &disposition.labels {
+        if let Some(window) = app.get_webview_window(label) {
+            let _ = window.close();
+            closed_window = true;
+        }
+    }
+
+    closed_window || disposition.removed_from_inventory
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        MAX_PREPARED_WINDOWS, MonitorPlacement, PREPARED_WINDOW_READY_TIMEOUT, PREPARED_WINDOW_TTL,
+        PreparedWindowDisposition, PreparedWindowReadiness, PreparedWindowRecycleReason,
+        PrewarmPoolMetrics, PrewarmPoolOutcome, ShutdownVerdict, ShutdownVerdictOutcome,
+        UserWindowPolicy, WindowCloseAction, WindowClosePolicy, WindowDescriptor,
+        WindowDescriptorError, WindowDescriptorRegistryBuilder, WindowInstancePolicy,
+        WindowLaunchPayload, WindowName, WindowPlacement, WindowRegistry, WindowStateEvent,
+        WindowStateRestore, cascaded_position_in_area, centered_position_in_area,
+        graceful_shutdown_target_labels, is_numeric_indexed_label, is_window_fully_within_area,
+        keeps_running_without_windows, position_update_interval, prepared_window_refill_count,
+        should_place_window, window_close_action_for_policy, window_kind_from_label,
+        window_kind_from_launch_target,
+    };
+    use std::time::{Duration, Instant};
+    use tauri::{PhysicalPosition, PhysicalSize};
+
+    #[test]
+    fn default_window_descriptors_pass_validation() {
+        assert!(super::window_kinds::window_descriptors().build().is_ok());
+    }
+
+    #[test]
+    fn every_window_name_is_listed_and_has_a_default_descriptor() {
+        let names = [WindowName::Main, WindowName::Support, WindowName::Settings];
+        for name in names {
+            // Stops compiling when a variant is added, until it is listed
+            // above and in `WindowName::ALL`.
+            match name {
+                WindowName::Main | WindowName::Support | WindowName::Settings => {}
+            }
+        }
+        assert_eq!(WindowName::ALL, names);
+
+        let registry = super::window_kinds::window_descriptors()
+            .build()
+            .expect("default window descriptors invalid");
+        for name in names {
+            assert_eq!(
+                registry
+                    .descriptors
+                    .iter()
+                    .filter(|descriptor| descriptor.name == name)
+                    .count(),
+                1,
+                "{name} needs exactly one descriptor"
+            );
+        }
+    }
+
+    #[test]
+    fn descriptors_with_overlapping_base_labels_are_rejected() {
+        let result = WindowDescriptorRegistryBuilder::default()
+            .window(
+                WindowDescriptor::new(WindowName::Main, "main")
+                    .user_window_policy(UserWindowPolicy::PrimaryAndIndexed),
+            )
+            .window(WindowDescriptor::new(WindowName::Support, "main-prewarm"))
+            .build();
+
+        assert_eq!(
+            result.err(),
+            Some(WindowDescriptorError::OverlappingBaseLabels {
+                label: "main-prewarm".to_string(),
+                conflicting_label: "main".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn descriptors_with_indexed_base_labels_of_another_kind_are_rejected() {
+        let result = WindowDescriptorRegistryBuilder::default()
+            .window(WindowDescriptor::new(WindowName::Support, "main-2"))
+            .window(WindowDescriptor::new(WindowName::Main, "main"))
+            .build();
+
+        assert!(matches!(
+            result,
+            Err(WindowDescriptorError::OverlappingBaseLabels { .. })
+        ));
+    }
+
+    #[test]
+    fn descriptors_must_cover_every_window_name_exactly_once() {
+        let duplicated = WindowDescriptorRegistryBuilder::default()
+            .window(WindowDescriptor::new(WindowName::Main, "main"))
+            .window(WindowDescriptor::new(WindowName::Main, "editor"))
+            .build();
+        assert_eq!(
+            duplicated.err(),
+            Some(WindowDescriptorError::DuplicateWindow(WindowName::Main))
+        );
+
+        let missing = WindowDescriptorRegistryBuilder::default()
+            .window(WindowDescriptor::new(WindowName::Main, "main"))
+            .build();
+        assert_eq!(
+            missing.err(),
+            Some(WindowDescriptorError::MissingWindow(WindowName::Support))
+        );
+    }
+
+    #[test]
+    fn descriptors_with_default_size_below_minimum_are_rejected() {
+        let result = WindowDescriptorRegistryBuilder::default()
+            .window(WindowDescriptor::new(WindowName::Main, "main").size(400.0, 300.0))
+            .window(WindowDescriptor::new(WindowName::Support, "support"))
+            .build();
+
+        assert_eq!(
+            result.err(),
+            Some(WindowDescriptorError::InvalidSize(WindowName::Main))
+        );
+    }
+
+    #[test]
+    fn descriptors_that_cascade_with_a_zero_step_are_rejected() {
+        let result = WindowDescriptorRegistryBuilder::default()
+            .window(
+                WindowDescriptor::new(WindowName::Main, "main")
+                    .placement(WindowPlacement::Cascade { step: 0 }),
+            )
+            .window(WindowDescriptor::new(WindowName::Support, "support"))
+            .build();
+
+        assert_eq!(
+            result.err(),
+            Some(WindowDescriptorError::EmptyCascadeStep(WindowName::Main))
+        );
+    }
+
+    #[test]
+    fn descriptors_with_the_same_launch_target_are_rejected() {
+        let result = WindowDescriptorRegistryBuilder::default()
+            .window(WindowDescriptor::new(WindowName::Main, "main").launch_target("main"))
+            .window(WindowDescriptor::new(WindowName::Support, "support").launch_target("Main"))
+            .build();
+
+        assert_eq!(
+            result.err(),
+            Some(WindowDescriptorError::DuplicateLaunchTarget(
+                "Main".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn launch_targets_resolve_to_their_window_kind() {
+        assert_eq!(
+            window_kind_from_launch_target("main"),
+            Some(WindowName::Main)
+        );
+        assert_eq!(
+            window_kind_from_launch_target("Support"),
+            Some(WindowName::Support)
+        );
+        assert_eq!(window_kind_from_launch_target("main-1"), None);
+        assert_eq!(window_kind_from_launch_target("settings"), None);
+    }
+
+    #[test]
+    fn owned_descriptors_must_be_plain_windows_built_with_their_owner() {
+        let descriptors = |settings: WindowDescriptor| {
+            WindowDescriptorRegistryBuilder::default()
+                .window(WindowDescriptor::new(WindowName::Main, "main"))
+                .window(WindowDescriptor::new(WindowName::Support, "support"))
+                .window(settings)
+                .build()
+                .err()
+        };
+
+        assert_eq!(
+            descriptors(WindowDescriptor::new(WindowName::Settings, "settings").modal(true)),
+            Some(WindowDescriptorError::ModalWithoutOwner(
+                WindowName::Settings
+            ))
+        );
+        assert_eq!(
+            descriptors(
+                WindowDescriptor::new(WindowName::Settings, "settings")
+                    .owned_by_opener(true)
+                    .user_window_policy(UserWindowPolicy::PrimaryAndIndexed)
+            ),
+            Some(WindowDescriptorError::OwnedUserWindow(WindowName::Settings))
+        );
+        assert_eq!(
+            descriptors(
+                WindowDescriptor::new(WindowName::Settings, "settings")
+                    .owned_by_opener(true)
+                    .prewarm(true)
+            ),
+            Some(WindowDescriptorError::OwnedPrewarmWindow(
+                WindowName::Settings
+            ))
+        );
+        assert_eq!(
+            descriptors(
+                WindowDescriptor::new(WindowName::Settings, "settings")
+                    .owned_by_opener(true)
+                    .close_policy(WindowClosePolicy::HideWindow)
+            ),
+            Some(WindowDescriptorError::OwnedHiddenWindow(
+                WindowName::Settings
+            ))
+        );
+        assert_eq!(
+            descriptors(
+                WindowDescriptor::new(WindowName::Settings, "settings")
+                    .owned_by_opener(true)
+                    .instance_policy(WindowInstancePolicy::Singleton)
+            ),
+            Some(WindowDescriptorError::OwnedSingletonWindow(
+                WindowName::Settings
+            ))
+        );
+        assert_eq!(
+            descriptors(
+                WindowDescriptor::new(WindowName::Settings, "settings").restore_session(true)
+            ),
+            Some(WindowDescriptorError::SessionRestoreWithoutUserWindow(
+                WindowName::Settings
+            ))
+        );
+        assert_eq!(
+            descriptors(
+                WindowDescriptor::new(WindowName::Settings, "settings")
+                    .owned_by_opener(true)
+                    .modal(true)
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn visible_main_labels_resolve_as_user_windows() {
+        assert_eq!(window_kind_from_label("main"), Some(WindowName::Main));
+        assert_eq!(window_kind_from_label("main-1"), Some(WindowName::Main));
+    }
+
+    #[test]
+    fn warm_window_owners_are_deduped_per_target_and_label() {
+        let registry = WindowRegistry::default();
+
+        assert!(registry.add_warm_window_owner(WindowName::Main, "main"));
+        assert!(!registry.add_warm_window_owner(WindowName::Main, "main"));
+        assert_eq!(
+            registry.active_warm_window_targets(),
+            vec![WindowName::Main]
+        );
+        assert!(registry.has_warm_window_owner(WindowName::Main));
+    }
+
+    #[test]
+    fn warm_window_target_stays_active_until_last_owner_leaves() {
+        let registry = WindowRegistry::default();
+
+        assert!(registry.add_warm_window_owner(WindowName::Main, "main"));
+        assert!(registry.add_warm_window_owner(WindowName::Main, "main-1"));
+        assert!(registry.remove_warm_window_owner(WindowName::Main, "main"));
+        assert!(registry.has_warm_window_owner(WindowName::Main));
+
+        assert!(!registry.remove_warm_window_owner(WindowName::Main, "main-1"));
+        assert!(!registry.has_warm_window_owner(WindowName::Main));
+        assert!(registry.active_warm_window_targets().is_empty());
+    }
+
+    #[test]
+    fn removing_owner_label_cleans_up_all_matching_warm_targets() {
+        let registry = WindowRegistry::default();
+
+        assert!(registry.add_warm_window_owner(WindowName::Main, "main"));
+        assert!(registry.add_warm_window_owner(WindowName::Support, "main"));
+
+        assert_eq!(
+            registry.remove_warm_window_owner_label("main"),
+            vec![WindowName::Main, WindowName::Support]
+        );
+        assert!(registry.active_warm_window_targets().is_empty());
+    }
+
+    #[test]
+    fn centered_position_clamps_to_area_origin_when_window_is_oversized() {
+        let position = centered_position_in_area(
+            PhysicalPosition::new(100, 120),
+            PhysicalSize::new(800, 600),
+            PhysicalSize::new(1200, 900),
+        );
+
+        assert_eq!(position, PhysicalPosition::new(100, 120));
+    }
+
+    #[test]
+    fn cascade_offsets_each_window_from_its_opener() {
+        let position = cascaded_position_in_area(
+            PhysicalPosition::new(200, 150),
+            32,
+            PhysicalSize::new(800, 600),
+            PhysicalPosition::new(0, 0),
+            PhysicalSize::new(1920, 1080),
+        );
+
+        assert_eq!(position, PhysicalPosition::new(232, 182));
+    }
+
+    #[test]
+    fn cascade_wraps_to_the_work_area_origin_at_its_edge() {
+        let position = cascaded_position_in_area(
+            PhysicalPosition::new(1100, 460),
+            32,
+            PhysicalSize::new(800, 600),
+            PhysicalPosition::new(0, 40),
+            PhysicalSize::new(1920, 1040),
+        );
+
+        assert_eq!(position, PhysicalPosition::new(0, 40));
+    }
+
+    #[test]
+    fn cascade_centers_windows_larger_than_the_work_area() {
+        let position = cascaded_position_in_area(
+            PhysicalPosition::new(0, 0),
+            32,
+            PhysicalSize::new(2000, 600),
+            PhysicalPosition::new(0, 0),
+            PhysicalSize::new(1920, 1080),
+        );
+
+        assert_eq!(position, PhysicalPosition::new(0, 240));
+    }
+
+    #[test]
+    fn restored_geometry_is_only_overridden_by_another_monitor() {
+        let cascade = WindowPlacement::Cascade { step: 32 };
+
+        assert!(!should_place_window(
+            WindowStateRestore::Restored,
+            false,
+            cascade,
+            Some(MonitorPlacement::OpenerMonitor)
+        ));
+        assert!(should_place_window(
+            WindowStateRestore::Restored,
+            false,
+            WindowPlacement::OpenerMonitor,
+            Some(MonitorPlacement::MonitorIndex(1))
+        ));
+        assert!(should_place_window(
+            WindowStateRestore::MonitorMissing,
+            false,
+            WindowPlacement::OpenerMonitor,
+            None
+        ));
+    }
+
+    #[test]
+    fn unsaved_windows_keep_their_default_position_unless_moved_or_cascaded() {
+        assert!(!should_place_window(
+            WindowStateRestore::NotSaved,
+            false,
+            WindowPlacement::OpenerMonitor,
+            None
+        ));
+        assert!(should_place_window(
+            WindowStateRestore::NotSaved,
+            true,
+            WindowPlacement::OpenerMonitor,
+            None
+        ));
+        assert!(should_place_window(
+            WindowStateRestore::NotSaved,
+            false,
+            WindowPlacement::Cascade { step: 32 },
+            None
+        ));
+        assert!(should_place_window(
+            WindowStateRestore::NotSaved,
+            false,
+            WindowPlacement::OpenerMonitor,
+            Some(MonitorPlacement::CursorMonitor)
+        ));
+    }
+
+    #[test]
+    fn window_visibility_check_uses_full_window_bounds_against_work_area() {
+        assert!(is_window_fully_within_area(
+            PhysicalPosition::new(120, 150),
+            PhysicalSize::new(400, 300),
+            PhysicalPosition::new(100, 120),
+            PhysicalSize::new(800, 600),
+        ));
+
+        assert!(!is_window_fully_within_area(
+            PhysicalPosition::new(700, 500),
+            PhysicalSize::new(400, 300),
+            PhysicalPosition::new(100, 120),
+            PhysicalSize::new(800, 600),
+        ));
+    }
+
+    #[test]
+    fn repeated_main_labels_stay_user_windows_without_support_identity() {
+        let registry = WindowRegistry::default();
+        assert_eq!(window_kind_from_label("main-2"), Some(WindowName::Main));
+        assert!(registry.should_label_resolve_as_user_window("main-2"));
+    }
+
+    #[test]
+    fn unknown_labels_do_not_resolve_as_user_windows() {
+        let registry = WindowRegistry::default();
+        assert!(!registry.should_label_resolve_as_user_window("unknown"));
+    }
+
+    #[test]
+    fn startup_window_kind_marks_primary_main_as_user_window() {
+        let registry = WindowRegistry::default();
+        let info = registry.window_kind_info_for_label("main");
+
+        assert_eq!(info.window, Some(WindowName::Main));
+        assert!(info.is_primary_window);
+        assert!(info.is_user_window);
+    }
+
+    #[test]
+    fn support_window_kind_marks_primary_support_without_user_ownership() {
+        let registry = WindowRegistry::default();
+        let info = registry.window_kind_info_for_label("support");
+
+        assert_eq!(info.window, Some(WindowName::Support));
+        assert!(info.is_primary_window);
+        assert!(!info.is_user_window);
+    }
+
+    #[test]
+    fn repeated_open_window_labels_remain_non_primary_user_windows() {
+        let registry = WindowRegistry::default();
+        let info = registry.window_kind_info_for_label("main-3");
+
+        assert_eq!(info.window, Some(WindowName::Main));
+        assert!(!info.is_primary_window);
+        assert!(info.is_user_window);
+    }
+
+    #[test]
+    fn support_like_labels_never_resolve_as_visible_user_windows() {
+        let registry = WindowRegistry::default();
+        for label in ["main-prewarm-1", "support-main", "prewarm-main"] {
+            let info = registry.window_kind_info_for_label(label);
+
+            assert_eq!(
+                window_kind_from_label(label),
+                Some(WindowName::Main).filter(|_| label.starts_with("main-"))
+            );
+            assert!(!info.is_primary_window);
+            assert!(!info.is_user_window);
+        }
+    }
+
+    #[test]
+    fn support_prewarm_labels_resolve_to_support_without_main_specific_rules() {
+        let registry = WindowRegistry::default();
+        let info = registry.window_kind_info_for_label("support-prewarm-1");
+
+        assert_eq!(info.window, Some(WindowName::Support));
+        assert!(!info.is_primary_window);
+        assert!(!info.is_user_window);
+    }
+
+    #[test]
+    fn only_numeric_main_suffixes_count_as_user_window_instances() {
+        assert!(is_numeric_indexed_label("main", "main-1"));
+        assert!(is_numeric_indexed_label("main", "main-42"));
+        assert!(!is_numeric_indexed_label("main", "main-prewarm-1"));
+        assert!(!is_numeric_indexed_label("main", "main-secondary"));
+        assert!(!is_numeric_indexed_label("main", "support-main-1"));
+    }
+
+    #[test]
+    fn startup_and_repeated_open_labels_classify_as_exactly_one_primary_user_window() {
+        let registry = WindowRegistry::default();
+        let infos = registry.classify_window_labels(["main", "main-1", "main-2"]);
+
+        assert_eq!(infos.len(), 3);
+        assert_eq!(
+            infos.iter().filter(|info| info.is_primary_window).count(),
+            1
+        );
+        assert!(infos.iter().all(|info| info.is_user_window));
+    }
+
+    #[test]
+    fn reopen_sequences_never_promote_secondary_labels_to_primary_main() {
+        let registry = WindowRegistry::default();
+        let infos =
+            registry.classify_window_labels(["main", "main-1", "main-2", "main-1", "main-3"]);
+
+        assert_eq!(
+            infos.iter().filter(|info| info.is_primary_window).count(),
+            1
+        );
+        assert!(
+            infos
+                .iter()
+                .filter(|info| info.label != "main")
+                .all(|info| !info.is_primary_window && info.is_user_window)
+        );
+    }
+
+    #[test]
+    fn authoritative_label_classification_stays_stable_across_known_cases() {
+        let registry = WindowRegistry::default();
+        let cases = [
+            ("main", Some(WindowName::Main), true, true),
+            ("main-1", Some(WindowName::Main), false, true),
+            ("main-99", Some(WindowName::Main), false, true),
+            ("main-prewarm-1", Some(WindowName::Main), false, false),
+            ("support-main", None, false, false),
+            ("prewarm-main", None, false, false),
+            ("unknown", None, false, false),
+        ];
+
+        for (label, expected_window, expected_primary, expected_user) in cases {
+            let identity = registry.classify_window_identity(label);
+
+            assert_eq!(
+                identity.window, expected_window,
+                "wrong window kind for {label}"
+            );
+            assert_eq!(
+                identity.is_primary_window, expected_primary,
+                "wrong primary classification for {label}"
+            );
+            assert_eq!(
+                identity.is_user_window, expected_user,
+                "wrong user-window classification for {label}"
+            );
+        }
+    }
+
+    #[test]
+    fn support_and_prewarm_labels_never_classify_as_user_windows_in_mixed_enumeration() {
+        let registry = WindowRegistry::default();
+        let infos = registry.classify_window_labels([
+            "main",
+            "main-prewarm-1",
+            "support-main",
+            "prewarm-main",
+            "main-1",
+        ]);
+
+        let visible_user_labels = infos
+            .iter()
+            .filter(|info| info.is_user_window)
+            .map(|info| info.label.as_str())
+            .collect::<Vec<_>>();
+
+        assert_eq!(visible_user_labels, vec!["main", "main-1"]);
+    }
+
+    #[test]
+    fn closing_one_of_multiple_user_windows_does_not_exit() {
+        let registry = WindowRegistry::default();
+        assert_eq!(
+            registry.window_close_action_after_visible_user_count("main-1", 2),
+            WindowCloseAction::Close
+        );
+        assert_eq!(
+            registry.window_close_action_after_visible_user_count("main", 3),
+            WindowCloseAction::Close
+        );
+    }
+
+    #[test]
+    fn closing_last_user_window_exits() {
+        let registry = WindowRegistry::default();
+        assert_eq!(
+            registry.window_close_action_after_visible_user_count("main", 1),
+            WindowCloseAction::Exit
+        );
+        assert_eq!(
+            registry.window_close_action_after_visible_user_count("main-1", 1),
+            WindowCloseAction::Exit
+        );
+    }
+
+    #[test]
+    fn reopen_close_accounting_only_exits_when_last_visible_user_window_closes() {
+        let registry = WindowRegistry::default();
+        assert_eq!(
+            registry.window_close_action_after_visible_user_count("main-2", 3),
+            WindowCloseAction::Close
+        );
+        assert_eq!(
+            registry.window_close_action_after_visible_user_count("main-1", 2),
+            WindowCloseAction::Close
+        );
+        assert_eq!(
+            registry.window_close_action_after_visible_user_count("main", 1),
+            WindowCloseAction::Exit
+        );
+        assert_eq!(
+            registry.window_close_action_after_visible_user_count("main-3", 1),
+            WindowCloseAction::Exit
+        );
+    }
+
+    #[test]
+    fn closing_support_or_prewarm_window_never_exits() {
+        let registry = WindowRegistry::default();
+        for label in ["main-prewarm-1", "support-main", "prewarm-main", "unknown"] {
+            assert_eq!(
+                registry.window_close_action_after_visible_user_count(label, 1),
+                WindowCloseAction::Close,
+                "label {label} should not participate in exit accounting"
+            );
+            assert_eq!(
+                registry.window_close_action_after_visible_user_count(label, 3),
+                WindowCloseAction::Close,
+                "label {label} should not participate in exit accounting"
+            );
+        }
+    }
+
+    #[test]
+    fn hide_policy_hides_the_shown_window_but_closes_prepared_ones() {
+        let registry = WindowRegistry::default();
+        assert_eq!(
+            registry.window_close_action_after_visible_user_count("settings", 1),
+            WindowCloseAction::Hide
+        );
+
+        let _ =
+            registry.reserve_prepared_window(WindowName::Settings, "settings-prewarm".to_string());
+        assert_eq!(
+            registry.window_close_action_after_visible_user_count("settings-prewarm", 1),
+            WindowCloseAction::Close
+        );
+    }
+
+    #[test]
+    fn keep_running_policy_keeps_the_process_alive_without_windows() {
+        assert!(!keeps_running_without_windows(
+            &super::window_kinds::window_descriptors().descriptors
+        ));
+        assert!(keeps_running_without_windows(&[
+            WindowDescriptor::new(WindowName::Main, "main"),
+            WindowDescriptor::new(WindowName::Support, "support")
+                .close_policy(WindowClosePolicy::KeepRunning),
+        ]));
+    }
+
+    #[test]
+    fn close_policy_decides_the_last_visible_user_window_close() {
+        assert_eq!(
+            window_close_action_for_policy(WindowClosePolicy::ExitOnLastWindow, true, 1),
+            WindowCloseAction::Exit
+        );
+        assert_eq!(
+            window_close_action_for_policy(WindowClosePolicy::HideWindow, true, 1),
+            WindowCloseAction::Hide
+        );
+        assert_eq!(
+            window_close_action_for_policy(WindowClosePolicy::KeepRunning, true, 1),
+            WindowCloseAction::Close
+        );
+        assert_eq!(
+            window_close_action_for_policy(WindowClosePolicy::ExitOnLastWindow, false, 1),
+            WindowCloseAction::Close
+        );
+    }
+
+    #[test]
+    fn owned_windows_never_count_toward_user_window_close_accounting() {
+        let registry = WindowRegistry::default();
+        registry.attach_owned_window("settings", "main", true);
+
+        assert!(!registry.should_label_resolve_as_user_window("settings"));
+        assert_eq!(
+            registry
+                .window_kind_info_for_label("settings")
+                .owner_label
+                .as_deref(),
+            Some("main")
+        );
+        assert_eq!(
+            registry.window_close_action_after_visible_user_count("settings", 1),
+            WindowCloseAction::Close
+        );
+        assert_eq!(
+            registry.window_close_action_after_visible_user_count("main", 1),
+            WindowCloseAction::Exit
+        );
+    }
+
+    #[test]
+    fn position_update_interval_defaults_and_clamps() {
+        assert_eq!(position_update_interval(None), Duration::from_millis(16));
+        assert_eq!(position_update_interval(Some(1)), Duration::from_millis(8));
+        assert_eq!(
+            position_update_interval(Some(60_000)),
+            Duration::from_secs(1)
+        );
+    }
+
+    #[test]
+    fn a_new_position_subscription_replaces_the_previous_one() {
+        let registry = WindowRegistry::default();
+        let first = registry.start_position_subscription("main");
+        let second = registry.start_position_subscription("main");
+
+        assert!(!registry.is_position_subscription_active("main", first));
+        assert!(registry.is_position_subscription_active("main", second));
+        assert!(registry.stop_position_subscription("main"));
+        assert!(!registry.stop_position_subscription("main"));
+        assert!(!registry.is_position_subscription_active("main", second));
+    }
+
+    #[test]
+    fn window_state_is_only_reported_when_it_changes() {
+        let registry = WindowRegistry::default();
+        let unfocused = WindowStateEvent {
+            is_fullscreen: false,
+            is_maximized: false,
+            is_minimized: false,
+            is_focused: false,
+        };
+        let focused = WindowStateEvent {
+            is_focused: true,
+            ..unfocused
+        };
+
+        assert!(registry.record_window_state("main", unfocused));
+        assert!(!registry.record_window_state("main", unfocused));
+        assert!(registry.record_window_state("main-1", unfocused));
+        assert!(registry.record_window_state("main", focused));
+        assert!(!registry.record_window_state("main", focused));
+
+        registry.handle_destroyed_window_state("main");
+        assert!(registry.record_window_state("main", focused));
+    }
+
+    #[test]
+    fn destroying_an_owner_releases_every_window_it_owns() {
+        let registry = WindowRegistry::default();
+        registry.attach_owned_window("settings", "main-2", true);
+        registry.attach_owned_window("settings-1", "main-2", false);
+        registry.attach_owned_window("settings-2", "main", true);
+
+        let release = registry.release_owned_windows("main-2");
+
+        assert_eq!(release.owned_labels, vec!["settings", "settings-1"]);
+        assert_eq!(release.unblocked_owner_label, None);
+        assert_eq!(
+            registry.window_kind_info_for_label("settings").owner_label,
+            None
+        );
+        assert_eq!(
+            registry
+                .window_kind_info_for_label("settings-2")
+                .owner_label
+                .as_deref(),
+            Some("main")
+        );
+    }
+
+    #[test]
+    fn an_owner_is_unblocked_once_its_last_modal_window_is_destroyed() {
+        let registry = WindowRegistry::default();
+        registry.attach_owned_window("settings", "main", true);
+        registry.attach_owned_window("settings-1", "main", true);
+        registry.attach_owned_window("settings-2", "main", false);
+
+        assert_eq!(
+            registry
+                .release_owned_windows("settings")
+                .unblocked_owner_label,
+            None
+        );
+        assert_eq!(
+            registry
+                .release_owned_windows("settings-2")
+                .unblocked_owner_label,
+            None
+        );
+        assert_eq!(
+            registry
+                .release_owned_windows("settings-1")
+                .unblocked_owner_label
+                .as_deref(),
+            Some("main")
+        );
+    }
+
+    #[test]
+    fn graceful_shutdown_targets_every_other_window_before_exit() {
+        let labels = graceful_shutdown_target_labels(
+            vec![
+                "main".to_string(),
+                "main-prewarm-1".to_string(),
+                "support".to_string(),
+            ],
+            "main",
+        );
+
+        assert_eq!(
+            labels,
+            vec!["main-prewarm-1".to_string(), "support".to_string()]
+        );
+    }
+
+    #[test]
+    fn graceful_shutdown_state_allows_only_one_initiator() {
+        let registry = WindowRegistry::default();
+
+        let request_id = registry
+            .try_request_graceful_shutdown(None, ["main".to_string()])
+            .expect("shutdown request refused");
+        assert!(!registry.try_begin_graceful_shutdown());
+
+        assert_eq!(
+            registry.record_shutdown_verdict(request_id, "main", ShutdownVerdict::Veto),
+            ShutdownVerdictOutcome::Vetoed
+        );
+        assert!(registry.try_begin_graceful_shutdown());
+    }
+
+    #[test]
+    fn close_requests_stop_triggering_exit_once_graceful_shutdown_has_started() {
+        let registry = WindowRegistry::default();
+        assert!(registry.try_begin_graceful_shutdown());
+
+        assert_eq!(
+            registry.window_close_action_after_visible_user_count("main", 1),
+            WindowCloseAction::Close
+        );
+        assert_eq!(
+            registry.window_close_action_after_visible_user_count("main-1", 1),
+            WindowCloseAction::Close
+        );
+    }
+
+    #[test]
+    fn destroyed_windows_drive_graceful_shutdown_to_completion() {
+        let registry = WindowRegistry::default();
+
+        assert!(
+            registry.start_graceful_shutdown_tracking(["main".to_string(), "main-1".to_string(),])
+        );
+        assert!(registry.is_graceful_shutdown_in_progress());
+        assert_eq!(
+            registry.graceful_shutdown_pending_labels(),
+            vec!["main".to_string(), "main-1".to_string()]
+        );
+
+        assert!(!registry.handle_destroyed_window_state("unknown"));
+        assert_eq!(
+            registry.graceful_shutdown_pending_labels(),
+            vec!["main".to_string(), "main-1".to_string()]
+        );
+
+        assert!(!registry.handle_destroyed_window_state("main"));
+        assert_eq!(
+            registry.graceful_shutdown_pending_labels(),
+            vec!["main-1".to_string()]
+        );
+        assert!(registry.is_graceful_shutdown_in_progress());
+
+        assert!(registry.handle_destroyed_window_state("main-1"));
+        assert!(registry.graceful_shutdown_pending_labels().is_empty());
+        assert!(!registry.is_graceful_shutdown_in_progress());
+    }
+
+    #[test]
+    fn a_shutdown_veto_rolls_the_request_back() {
+        let registry = WindowRegistry::default();
+        let request_id = registry
+            .try_request_graceful_shutdown(Some("main"), ["main".to_string(), "main-1".to_string()])
+            .expect("shutdown request refused");
+        assert_eq!(
+            registry.try_request_graceful_shutdown(None, std::iter::empty()),
+            None
+        );
+
+        assert_eq!(
+            registry.record_shutdown_verdict(request_id, "main", ShutdownVerdict::Proceed),
+            ShutdownVerdictOutcome::Waiting
+        );
+        assert_eq!(
+            registry.record_shutdown_verdict(request_id, "main-1", ShutdownVerdict::Veto),
+            ShutdownVerdictOutcome::Vetoed
+        );
+        assert!(!registry.is_graceful_shutdown_in_progress());
+        assert_eq!(
+            registry.finish_shutdown_request(request_id, ["main".to_string()]),
+            None
+        );
+        assert_eq!(
+            registry.window_close_action_after_visible_user_count("main", 1),
+            WindowCloseAction::Exit
+        );
+
+        assert_eq!(
+            registry.try_request_graceful_shutdown(None, ["main".to_string()]),
+            Some(request_id + 1)
+        );
+    }
+
+    #[test]
+    fn an_approved_shutdown_request_moves_on_to_closing_windows() {
+        let registry = WindowRegistry::default();
+        let request_id = registry
+            .try_request_graceful_shutdown(Some("main"), ["main".to_string(), "main-1".to_string()])
+            .expect("shutdown request refused");
+
+        // Close requests keep following the close policy until the windows
+        // have agreed, since nothing is closing yet.
+        assert_eq!(
+            registry.window_close_action_after_visible_user_count("main", 1),
+            WindowCloseAction::Exit
+        );
+
+        assert_eq!(
+            registry.record_shutdown_verdict(request_id, "main", ShutdownVerdict::Proceed),
+            ShutdownVerdictOutcome::Waiting
+        );
+        assert!(!registry.handle_destroyed_window_state("main-1"));
+        assert_eq!(registry.approved_shutdown_request(), Some(request_id));
+
+        assert_eq!(
+            registry
+                .finish_shutdown_request(request_id, ["main".to_string(), "support".to_string()]),
+            Some(Some("main".to_string()))
+        );
+        assert_eq!(
+            registry.graceful_shutdown_pending_labels(),
+            vec!["main".to_string(), "support".to_string()]
+        );
+        assert_eq!(
+            registry.window_close_action_after_visible_user_count("main", 1),
+            WindowCloseAction::Close
+        );
+        assert_eq!(
+            registry.finish_shutdown_request(request_id, std::iter::empty()),
+            None
+        );
+    }
+
+    #[test]
+    fn stray_shutdown_verdicts_are_ignored() {
+        let registry = WindowRegistry::default();
+        assert_eq!(
+            registry.record_shutdown_verdict(1, "main", ShutdownVerdict::Veto),
+            ShutdownVerdictOutcome::Ignored
+        );
+
+        let request_id = registry
+            .try_request_graceful_shutdown(None, ["main".to_string(), "main-1".to_string()])
+            .expect("shutdown request refused");
+        assert_eq!(
+            registry.record_shutdown_verdict(request_id + 1, "main", ShutdownVerdict::Veto),
+            ShutdownVerdictOutcome::Ignored
+        );
+        assert_eq!(
+            registry.record_shutdown_verdict(request_id, "support", ShutdownVerdict::Veto),
+            ShutdownVerdictOutcome::Ignored
+        );
+        assert_eq!(
+            registry.record_shutdown_verdict(request_id, "main", ShutdownVerdict::Proceed),
+            ShutdownVerdictOutcome::Waiting
+        );
+        assert_eq!(
+            registry.record_shutdown_verdict(request_id, "main", ShutdownVerdict::Veto),
+            ShutdownVerdictOutcome::Ignored
+        );
+        assert_eq!(
+            registry.graceful_shutdown_awaiting_labels(),
+            vec!["main-1".to_string()]
+        );
+    }
+
+    #[test]
+    fn destroyed_prepared_window_is_removed_from_inventory() {
+        let registry = WindowRegistry::default();
+
+        registry.reserve_prepared_window(WindowName::Main, "main-prewarm".to_string());
+
+        assert!(!registry.handle_destroyed_window_state("main-prewarm"));
+        assert!(registry.prepared_window_targets().is_empty());
+        assert_eq!(registry.prepared_window_label(WindowName::Main), None);
+    }
+
+    #[test]
+    fn destroyed_promoted_window_label_is_demoted_from_user_window_state() {
+        let registry = WindowRegistry::default();
+
+        registry.promote_window_label_to_user_window("main-prewarm");
+        assert!(registry.should_label_resolve_as_user_window("main-prewarm"));
+
+        assert!(!registry.handle_destroyed_window_state("main-prewarm"));
+        assert!(!registry.should_label_resolve_as_user_window("main-prewarm"));
+    }
+
+    #[test]
+    fn typed_prewarm_targets_are_keyed_by_window_enum() {
+        let registry = WindowRegistry::default();
+
+        registry.reserve_prepared_window(WindowName::Main, "main-prewarm".to_string());
+
+        assert_eq!(registry.prepared_window_targets(), vec![WindowName::Main]);
+
+        registry.reserve_prepared_window(WindowName::Main, "main-prewarm".to_string());
+
+        assert_eq!(registry.prepared_window_targets(), vec![WindowName::Main]);
+    }
+
+    #[test]
+    fn authoritative_discard_uses_descriptor_prewarm_label_for_support_windows() {
+        let registry = WindowRegistry::default();
+
+        assert_eq!(
+            registry.discard_prepared_window_state(WindowName::Support),
+            PreparedWindowDisposition {
+                labels: vec!["support-prewarm".to_string()],
+                removed_from_inventory: false,
+            }
+        );
+    }
+
+    #[test]
+    fn discarded_prepared_targets_are_removed_from_inventory() {
+        let registry = WindowRegistry::default();
+        registry.reserve_prepared_window(WindowName::Main, "main-prewarm".to_string());
 
-    if let Some(prepared_window) = take_prepared_window(name) {
-        if let Some(window_to_show) = app.get_webview_window(&prepared_window.label) {
-            promote_window_label_to_user_window(&prepared_window.label);
-            let _ = apply_window_options(&window_to_show, options.as_ref());
-            ensure_window_visible_on_opener_monitor(&window_to_show, &window);
-            activate_window(&window_to_show);
-            if has_warm_window_owner(name) {
-                prewarm_window(app.clone(), name);
-            }
-            let next_window_kind = window_kind_info_for_label(&prepared_window.label);
-            let _ = window_to_show.emit(WINDOW_KIND_CHANGED_EVENT, next_window_kind);
-            return;
-        }
+        assert!(registry.discard_prepared_window(WindowName::Main));
+        assert!(registry.prepared_window_targets().is_empty());
     }
 
-    let label = next_visible_label(name, &app);
-    match build_window(&app, label, descriptor, true) {
-        Ok(window_to_show) => {
-            let did_resize = apply_window_options(&window_to_show, options.as_ref());
-            if did_resize {
-                ensure_window_visible_on_opener_monitor(&window_to_show, &window);
+    #[test]
+    fn authoritative_discard_returns_existing_inventory_label() {
+        let registry = WindowRegistry::default();
+        registry.reserve_prepared_window(WindowName::Main, "main-prewarm".to_string());
+
+        assert_eq!(
+            registry.discard_prepared_window_state(WindowName::Main),
+            PreparedWindowDisposition {
+                labels: vec!["main-prewarm".to_string()],
+                removed_from_inventory: true,
             }
-            apply_window_setup(
-                &window_to_show,
-                descriptor.uses_primary_window_setup
-                    && descriptor.is_primary_label(window_to_show.label()),
-            );
-            activate_window(&window_to_show);
-        }
-        Err(error) => {
-            eprintln!("Failed to create window: {error}");
-        }
+        );
+        assert!(registry.prepared_window_targets().is_empty());
     }
-}
 
-#[specta::specta]
-#[tauri::command]
-pub fn warm_window(app: tauri::AppHandle, window: WebviewWindow, name: WindowName) {
-    let label = window.label().to_string();
-    if !should_label_resolve_as_user_window(&label) {
-        return;
+    #[test]
+    fn authoritative_discard_targets_canonical_label_even_without_inventory_entry() {
+        let registry = WindowRegistry::default();
+
+        assert_eq!(
+            registry.discard_prepared_window_state(WindowName::Main),
+            PreparedWindowDisposition {
+                labels: vec!["main-prewarm".to_string()],
+                removed_from_inventory: false,
+            }
+        );
     }
 
-    let _ = add_warm_window_owner(name, &label);
-    prewarm_window(app, name);
-}
+    #[test]
+    fn discarded_prepared_targets_stay_absent_during_future_open_flow() {
+        let registry = WindowRegistry::default();
+        registry.reserve_prepared_window(WindowName::Main, "main-prewarm".to_string());
 
-#[specta::specta]
-#[tauri::command]
-pub fn cold_window(app: tauri::AppHandle, window: WebviewWindow, name: WindowName) -> bool {
-    let label = window.label().to_string();
-    if remove_warm_window_owner(name, &label) {
-        return false;
+        assert!(registry.discard_prepared_window(WindowName::Main));
+
+        let consumed_prepared_target = registry.take_prepared_window(WindowName::Main);
+
+        assert!(consumed_prepared_target.is_none());
+        assert!(registry.prepared_window_targets().is_empty());
     }
 
-    discard_prewarm_window(app, name)
-}
+    #[test]
+    fn prewarm_creates_real_hidden_backend_window_state() {
+        let registry = WindowRegistry::default();
 
-#[specta::specta]
-#[tauri::command]
-pub fn prewarm_window(app: tauri::AppHandle, name: WindowName) {
-    let descriptor = window_descriptor(name);
-    if !descriptor.prewarm_enabled || !has_warm_window_owner(name) {
-        return;
+        registry.reserve_prepared_window(WindowName::Main, "main-prewarm".to_string());
+
+        let prepared = registry.prepared_window_targets();
+        assert_eq!(prepared, vec![WindowName::Main]);
+        assert_eq!(
+            registry.prepared_window_label(WindowName::Main).as_deref(),
+            Some("main-prewarm")
+        );
+        assert_eq!(
+            registry.prepared_window_readiness(WindowName::Main),
+            Some(PreparedWindowReadiness::Created)
+        );
+        assert!(registry.take_prepared_window(WindowName::Main).is_none());
+        assert_eq!(registry.prepared_window_targets(), vec![WindowName::Main]);
     }
 
-    let already_prepared_label = {
-        let inventory = prepared_window_inventory()
-            .lock()
-            .expect("prepared window inventory poisoned");
-        inventory.get(&name).map(|state| state.label.clone())
-    };
+    #[test]
+    fn taking_prepared_window_consumes_authoritative_backend_state_once() {
+        let registry = WindowRegistry::default();
+        registry.reserve_prepared_window(WindowName::Main, "main-prewarm".to_string());
 
-    if let Some(label) = already_prepared_label {
-        if app.get_webview_window(&label).is_some() {
-            return;
-        }
+        assert!(registry.mark_prepared_window_ready(WindowName::Main, "main-prewarm"));
 
-        let _ = discard_prepared_window_state(name);
+        assert!(registry.take_prepared_window(WindowName::Main).is_some());
+        assert!(registry.take_prepared_window(WindowName::Main).is_none());
     }
 
-    let label = next_prewarm_label(name, &app);
-    demote_window_label_from_user_window(&label);
-    let scheduled_label = label.clone();
-    let scheduled_label_for_task = scheduled_label.clone();
-    let app_handle = app.clone();
-    tauri::async_runtime::spawn(async move {
-        let run_result = app_handle.run_on_main_thread(move || {
-            if !has_warm_window_owner(name) {
-                return;
-            }
+    #[test]
+    fn merely_created_hidden_window_does_not_count_as_consumable_prewarm_inventory() {
+        let registry = WindowRegistry::default();
+        registry.reserve_prepared_window(WindowName::Main, "main-prewarm".to_string());
 
-            match build_window(&app, label.clone(), descriptor, false) {
-                Ok(window) => {
-                    if !has_warm_window_owner(name) {
-                        let _ = window.close();
-                        return;
-                    }
+        assert_eq!(
+            registry.prepared_window_readiness(WindowName::Main),
+            Some(PreparedWindowReadiness::Created)
+        );
+        assert!(registry.take_prepared_window(WindowName::Main).is_none());
+        assert_eq!(registry.prepared_window_targets(), vec![WindowName::Main]);
+    }
 
-                    apply_window_setup(&window, false);
-                    reserve_prepared_window(name, label);
-                }
-                Err(error) => {
-                    eprintln!("Failed to prewarm window: {error}");
-                }
-            }
-        });
+    #[test]
+    fn ready_hidden_window_becomes_consumable_for_next_visible_open_flow() {
+        let registry = WindowRegistry::default();
+        registry.reserve_prepared_window(WindowName::Main, "main-prewarm".to_string());
 
-        if let Err(error) = run_result {
-            eprintln!(
-                "Failed to schedule prewarm window {scheduled_label_for_task} for {name}: {error}"
-            );
-        }
-    });
-}
+        assert!(registry.mark_prepared_window_ready(WindowName::Main, "main-prewarm"));
+        assert_eq!(
+            registry.prepared_window_readiness(WindowName::Main),
+            Some(PreparedWindowReadiness::Ready)
+        );
 
-#[specta::specta]
-#[tauri::command]
-pub fn discard_prewarm_window(app: tauri::AppHandle, name: WindowName) -> bool {
-    let Some(disposition) = discard_prepared_window_state(name) else {
-        return false;
-    };
-    demote_window_label_from_user_window(&disposition.label);
+        let consumed = registry.take_prepared_window(WindowName::Main);
+        assert_eq!(
+            consumed.map(|state| (state.label, state.readiness)),
+            Some(("main-prewarm".to_string(), PreparedWindowReadiness::Ready))
+        );
+        assert!(registry.prepared_window_targets().is_empty());
+    }
 
-    if let Some(window) = app.get_webview_window(&disposition.label) {
-        let _ = window.close();
-        return true;
+    #[test]
+    fn readiness_updates_only_for_matching_hidden_inventory_label() {
+        let registry = WindowRegistry::default();
+        registry.reserve_prepared_window(WindowName::Main, "main-prewarm".to_string());
+
+        assert!(!registry.mark_prepared_window_ready(WindowName::Main, "main-1"));
+        assert_eq!(
+            registry.prepared_window_readiness(WindowName::Main),
+            Some(PreparedWindowReadiness::Created)
+        );
     }
 
-    disposition.removed_from_inventory
-}
+    #[test]
+    fn renderer_bootstrap_ready_makes_matching_hidden_window_consumable() {
+        let registry = WindowRegistry::default();
+        registry.reserve_prepared_window(WindowName::Main, "main-prewarm".to_string());
 
-#[cfg(test)]
-mod tests {
-    use super::{
-        PreparedWindowDisposition, PreparedWindowReadiness, PreparedWindowState, WindowName,
-        active_warm_window_targets, add_warm_window_owner, centered_position_in_area,
-        classify_window_identity, classify_window_labels, demote_window_label_from_user_window,
-        discard_prepared_window, discard_prepared_window_state, graceful_shutdown_pending_labels,
-        graceful_shutdown_target_labels, handle_destroyed_window_state, has_warm_window_owner,
-        is_graceful_shutdown_in_progress, is_numeric_indexed_label, is_user_window_label,
-        is_window_fully_within_area, mark_prepared_window_ready, prepared_window_label,
-        prepared_window_readiness, prepared_window_targets, promote_window_label_to_user_window,
-        remove_warm_window_owner, remove_warm_window_owner_label, reserve_prepared_window,
-        reset_graceful_shutdown_state, reset_prepared_window_inventory,
-        should_activate_window_on_app_ready, should_exit_on_window_close_with_count,
-        should_label_resolve_as_user_window, start_graceful_shutdown_tracking,
-        take_prepared_window, try_begin_graceful_shutdown, window_kind_from_label,
-        window_kind_info_for_label,
-    };
-    use std::sync::{Mutex, MutexGuard, OnceLock};
-    use tauri::{PhysicalPosition, PhysicalSize};
+        assert!(registry.mark_prepared_window_ready(WindowName::Main, "main-prewarm"));
+        assert_eq!(
+            registry.prepared_window_readiness(WindowName::Main),
+            Some(PreparedWindowReadiness::Ready)
+        );
+    }
 
-    fn test_state_guard() -> MutexGuard<'static, ()> {
-        static TEST_STATE_GUARD: OnceLock<Mutex<()>> = OnceLock::new();
-        TEST_STATE_GUARD
-            .get_or_init(|| Mutex::new(()))
-            .lock()
-            .expect("test state guard poisoned")
+    #[test]
+    fn consumed_prewarm_label_can_be_promoted_to_user_window() {
+        let registry = WindowRegistry::default();
+
+        assert!(!registry.should_label_resolve_as_user_window("main-prewarm"));
+        registry.promote_window_label_to_user_window("main-prewarm");
+        assert!(registry.should_label_resolve_as_user_window("main-prewarm"));
+
+        registry.demote_window_label_from_user_window("main-prewarm");
+        assert!(!registry.should_label_resolve_as_user_window("main-prewarm"));
     }
 
     #[test]
-    fn visible_main_labels_resolve_as_user_windows() {
-        assert_eq!(window_kind_from_label("main"), Some(WindowName::Main));
-        assert_eq!(window_kind_from_label("main-1"), Some(WindowName::Main));
+    fn app_ready_activation_skips_hidden_prewarm_labels() {
+        let registry = WindowRegistry::default();
+        assert!(registry.should_activate_window_on_app_ready("main"));
+        assert!(registry.should_activate_window_on_app_ready("main-1"));
+        assert!(!registry.should_activate_window_on_app_ready("main-prewarm"));
     }
 
     #[test]
-    fn warm_window_owners_are_deduped_per_target_and_label() {
-        let _guard = test_state_guard();
-        reset_prepared_window_inventory();
+    fn inventory_backed_hidden_window_classifies_as_prepared_without_user_ownership() {
+        let registry = WindowRegistry::default();
+        registry.reserve_prepared_window(WindowName::Main, "main-prewarm".to_string());
 
-        assert!(add_warm_window_owner(WindowName::Main, "main"));
-        assert!(!add_warm_window_owner(WindowName::Main, "main"));
-        assert_eq!(active_warm_window_targets(), vec![WindowName::Main]);
-        assert!(has_warm_window_owner(WindowName::Main));
+        let info = registry.window_kind_info_for_label("main-prewarm");
+        assert_eq!(info.window, Some(WindowName::Main));
+        assert!(!info.is_user_window);
+        assert!(info.is_prepared_window);
     }
 
     #[test]
-    fn warm_window_target_stays_active_until_last_owner_leaves() {
-        let _guard = test_state_guard();
-        reset_prepared_window_inventory();
+    fn consuming_hidden_window_removes_prepared_state_before_user_promotion() {
+        let registry = WindowRegistry::default();
+        registry.reserve_prepared_window(WindowName::Main, "main-prewarm".to_string());
+        assert!(registry.mark_prepared_window_ready(WindowName::Main, "main-prewarm"));
+
+        let consumed = registry.take_prepared_window(WindowName::Main);
+        assert!(consumed.is_some());
 
-        assert!(add_warm_window_owner(WindowName::Main, "main"));
-        assert!(add_warm_window_owner(WindowName::Main, "main-1"));
-        assert!(remove_warm_window_owner(WindowName::Main, "main"));
-        assert!(has_warm_window_owner(WindowName::Main));
+        let before_promotion = registry.window_kind_info_for_label("main-prewarm");
+        assert!(!before_promotion.is_user_window);
+        assert!(!before_promotion.is_prepared_window);
 
-        assert!(!remove_warm_window_owner(WindowName::Main, "main-1"));
-        assert!(!has_warm_window_owner(WindowName::Main));
-        assert!(active_warm_window_targets().is_empty());
+        registry.promote_window_label_to_user_window("main-prewarm");
+        let after_promotion = registry.window_kind_info_for_label("main-prewarm");
+        assert!(after_promotion.is_user_window);
+        assert!(!after_promotion.is_prepared_window);
     }
 
     #[test]
-    fn removing_owner_label_cleans_up_all_matching_warm_targets() {
-        let _guard = test_state_guard();
-        reset_prepared_window_inventory();
+    fn taking_ready_prepared_window_promotes_it_in_the_same_transition() {
+        let registry = WindowRegistry::default();
+        registry.reserve_prepared_window(WindowName::Main, "main-prewarm".to_string());
 
-        assert!(add_warm_window_owner(WindowName::Main, "main"));
-        assert!(add_warm_window_owner(WindowName::Support, "main"));
+        assert_eq!(
+            registry.take_prepared_window_and_promote(WindowName::Main),
+            None
+        );
+        assert!(registry.mark_prepared_window_ready(WindowName::Main, "main-prewarm"));
 
         assert_eq!(
-            remove_warm_window_owner_label("main"),
-            vec![WindowName::Main, WindowName::Support]
+            registry.take_prepared_window_and_promote(WindowName::Main),
+            Some("main-prewarm".to_string())
+        );
+        let info = registry.window_kind_info_for_label("main-prewarm");
+        assert!(info.is_user_window);
+        assert!(!info.is_prepared_window);
+        assert_eq!(
+            registry.take_prepared_window_and_promote(WindowName::Main),
+            None
         );
-        assert!(active_warm_window_targets().is_empty());
     }
 
     #[test]
-    fn centered_position_clamps_to_area_origin_when_window_is_oversized() {
-        let position = centered_position_in_area(
-            PhysicalPosition::new(100, 120),
-            PhysicalSize::new(800, 600),
-            PhysicalSize::new(1200, 900),
-        );
+    fn separate_registries_never_share_lifecycle_state() {
+        let first = WindowRegistry::default();
+        let second = WindowRegistry::default();
 
-        assert_eq!(position, PhysicalPosition::new(100, 120));
-    }
+        assert!(first.add_warm_window_owner(WindowName::Main, "main"));
+        assert!(first.start_graceful_shutdown_tracking(["main".to_string()]));
 
-    #[test]
-    fn window_visibility_check_uses_full_window_bounds_against_work_area() {
-        assert!(is_window_fully_within_area(
-            PhysicalPosition::new(120, 150),
-            PhysicalSize::new(400, 300),
-            PhysicalPosition::new(100, 120),
-            PhysicalSize::new(800, 600),
-        ));
+        assert!(!second.has_warm_window_owner(WindowName::Main));
+        assert!(!second.is_graceful_shutdown_in_progress());
+        assert!(second.add_warm_window_owner(WindowName::Main, "main"));
+    }
 
-        assert!(!is_window_fully_within_area(
-            PhysicalPosition::new(700, 500),
-            PhysicalSize::new(400, 300),
-            PhysicalPosition::new(100, 120),
-            PhysicalSize::new(800, 600),
-        ));
+    fn launch_payload(route: &str) -> WindowLaunchPayload {
+        WindowLaunchPayload {
+            route: Some(route.to_string()),
+            ..WindowLaunchPayload::default()
+        }
     }
 
     #[test]
-    fn repeated_main_labels_stay_user_windows_without_support_identity() {
-        assert_eq!(window_kind_from_label("main-2"), Some(WindowName::Main));
-        assert!(should_label_resolve_as_user_window("main-2"));
-        assert!(is_user_window_label("main-2"));
+    fn launch_payload_is_delivered_to_its_window_exactly_once() {
+        let registry = WindowRegistry::default();
+        registry.store_window_launch_payload("main-1", Some(launch_payload("/notes/1")));
+
+        assert_eq!(
+            registry.take_launch_payload_for_label("main-1"),
+            Some(launch_payload("/notes/1"))
+        );
+        assert_eq!(registry.take_launch_payload_for_label("main-1"), None);
     }
 
     #[test]
-    fn unknown_labels_do_not_resolve_as_user_windows() {
-        assert!(!should_label_resolve_as_user_window("unknown"));
-        assert!(!is_user_window_label("unknown"));
+    fn prepared_window_cannot_take_payload_before_promotion() {
+        let registry = WindowRegistry::default();
+        registry.reserve_prepared_window(WindowName::Main, "main-prewarm".to_string());
+        registry.store_window_launch_payload("main-prewarm", Some(launch_payload("/notes/2")));
+
+        assert_eq!(registry.take_launch_payload_for_label("main-prewarm"), None);
+
+        assert!(registry.mark_prepared_window_ready(WindowName::Main, "main-prewarm"));
+        assert!(registry.take_prepared_window(WindowName::Main).is_some());
+        registry.promote_window_label_to_user_window("main-prewarm");
+
+        assert_eq!(
+            registry.take_launch_payload_for_label("main-prewarm"),
+            Some(launch_payload("/notes/2"))
+        );
     }
 
     #[test]
-    fn startup_window_kind_marks_primary_main_as_user_window() {
-        let info = window_kind_info_for_label("main");
+    fn destroyed_window_drops_its_undelivered_launch_payload() {
+        let registry = WindowRegistry::default();
+        registry.store_window_launch_payload("main-3", Some(launch_payload("/notes/3")));
 
-        assert_eq!(info.window, Some(WindowName::Main));
-        assert!(info.is_primary_window);
-        assert!(info.is_user_window);
+        assert!(!registry.handle_destroyed_window_state("main-3"));
+        assert_eq!(registry.take_launch_payload_for_label("main-3"), None);
     }
 
     #[test]
-    fn support_window_kind_marks_primary_support_without_user_ownership() {
-        let info = window_kind_info_for_label("support");
-
-        assert_eq!(info.window, Some(WindowName::Support));
-        assert!(info.is_primary_window);
-        assert!(!info.is_user_window);
+    fn prewarm_pool_refill_is_bounded_by_pool_size_and_global_cap() {
+        assert_eq!(prepared_window_refill_count(2, 0, 0), 2);
+        assert_eq!(prepared_window_refill_count(2, 1, 1), 1);
+        assert_eq!(prepared_window_refill_count(2, 2, 2), 0);
+        assert_eq!(
+            prepared_window_refill_count(3, 0, MAX_PREPARED_WINDOWS - 1),
+            1
+        );
+        assert_eq!(prepared_window_refill_count(3, 0, MAX_PREPARED_WINDOWS), 0);
     }
 
     #[test]
-    fn repeated_open_window_labels_remain_non_primary_user_windows() {
-        let info = window_kind_info_for_label("main-3");
+    fn scheduling_prewarm_pool_claims_distinct_free_labels() {
+        let registry = WindowRegistry::default();
 
-        assert_eq!(info.window, Some(WindowName::Main));
-        assert!(!info.is_primary_window);
-        assert!(info.is_user_window);
+        let labels = registry
+            .schedule_prepared_windows(WindowName::Main, 2, |label| label != "main-prewarm");
+        assert_eq!(
+            labels,
+            vec!["main-prewarm-1".to_string(), "main-prewarm-2".to_string()]
+        );
+        assert!(registry.is_label_reserved_for_prepared_window("main-prewarm-1"));
+
+        assert!(
+            registry
+                .schedule_prepared_windows(WindowName::Main, 2, |_| true)
+                .is_empty()
+        );
+        assert_eq!(
+            registry.schedule_prepared_windows(WindowName::Main, 3, |_| true),
+            vec!["main-prewarm".to_string()]
+        );
     }
 
     #[test]
-    fn support_like_labels_never_resolve_as_visible_user_windows() {
-        for label in ["main-prewarm-1", "support-main", "prewarm-main"] {
-            let info = window_kind_info_for_label(label);
+    fn scheduled_prewarm_slots_are_not_consumable_until_ready() {
+        let registry = WindowRegistry::default();
+        let labels = registry.schedule_prepared_windows(WindowName::Main, 2, |_| true);
 
-            assert_eq!(
-                window_kind_from_label(label),
-                Some(WindowName::Main).filter(|_| label.starts_with("main-"))
-            );
-            assert!(!info.is_primary_window);
-            assert!(!info.is_user_window);
-        }
+        assert_eq!(
+            registry.prepared_window_readiness(WindowName::Main),
+            Some(PreparedWindowReadiness::Scheduled)
+        );
+        assert!(registry.take_prepared_window(WindowName::Main).is_none());
+
+        registry.reserve_prepared_window(WindowName::Main, labels[0].clone());
+        registry.reserve_prepared_window(WindowName::Main, labels[1].clone());
+        assert!(registry.mark_prepared_window_ready(WindowName::Main, &labels[1]));
+
+        assert_eq!(
+            registry
+                .take_prepared_window(WindowName::Main)
+                .map(|state| state.label),
+            Some(labels[1].clone())
+        );
+        assert_eq!(
+            registry.prepared_window_label(WindowName::Main),
+            Some(labels[0].clone())
+        );
     }
 
     #[test]
-    fn support_prewarm_labels_resolve_to_support_without_main_specific_rules() {
-        let info = window_kind_info_for_label("support-prewarm-1");
+    fn discarding_prewarm_pool_releases_every_slot_including_scheduled_ones() {
+        let registry = WindowRegistry::default();
+        let labels = registry.schedule_prepared_windows(WindowName::Main, 2, |_| true);
+        registry.reserve_prepared_window(WindowName::Main, labels[0].clone());
 
-        assert_eq!(info.window, Some(WindowName::Support));
-        assert!(!info.is_primary_window);
-        assert!(!info.is_user_window);
+        assert_eq!(
+            registry.discard_prepared_window_state(WindowName::Main),
+            PreparedWindowDisposition {
+                labels: labels.clone(),
+                removed_from_inventory: true,
+            }
+        );
+        assert!(
+            labels
+                .iter()
+                .all(|label| !registry.is_label_reserved_for_prepared_window(label))
+        );
+        assert!(registry.take_prepared_window(WindowName::Main).is_none());
     }
 
     #[test]
-    fn only_numeric_main_suffixes_count_as_user_window_instances() {
-        assert!(is_numeric_indexed_label("main", "main-1"));
-        assert!(is_numeric_indexed_label("main", "main-42"));
-        assert!(!is_numeric_indexed_label("main", "main-prewarm-1"));
-        assert!(!is_numeric_indexed_label("main", "main-secondary"));
-        assert!(!is_numeric_indexed_label("main", "support-main-1"));
+    fn prewarm_pool_metrics_count_hits_and_cold_opens_per_target() {
+        let registry = WindowRegistry::default();
+
+        registry.record_prewarm_pool_outcome(WindowName::Main, PrewarmPoolOutcome::Hit);
+        registry.record_prewarm_pool_outcome(WindowName::Main, PrewarmPoolOutcome::ColdOpen);
+        registry.record_prewarm_pool_outcome(WindowName::Main, PrewarmPoolOutcome::Hit);
+        registry.record_prewarm_pool_outcome(WindowName::Support, PrewarmPoolOutcome::ColdOpen);
+        registry.record_prewarm_pool_outcome(WindowName::Support, PrewarmPoolOutcome::Recycled);
+
+        assert_eq!(
+            registry.prewarm_pool_metrics(),
+            vec![
+                PrewarmPoolMetrics {
+                    window: WindowName::Main,
+                    pool_hits: 2,
+                    cold_opens: 1,
+                    recycled: 0,
+                },
+                PrewarmPoolMetrics {
+                    window: WindowName::Support,
+                    pool_hits: 0,
+                    cold_opens: 1,
+                    recycled: 1,
+                },
+            ]
+        );
     }
 
     #[test]
-    fn startup_and_repeated_open_labels_classify_as_exactly_one_primary_user_window() {
-        let infos = classify_window_labels(["main", "main-1", "main-2"]);
+    fn prepared_windows_stuck_before_ready_are_reported_after_timeout() {
+        let registry = WindowRegistry::default();
+        registry.reserve_prepared_window(WindowName::Main, "main-prewarm".to_string());
 
-        assert_eq!(infos.len(), 3);
+        assert!(registry.stale_prepared_windows(Instant::now()).is_empty());
         assert_eq!(
-            infos.iter().filter(|info| info.is_primary_window).count(),
-            1
+            registry.stale_prepared_windows(Instant::now() + PREPARED_WINDOW_READY_TIMEOUT),
+            vec![(
+                WindowName::Main,
+                "main-prewarm".to_string(),
+                PreparedWindowRecycleReason::ReadyTimeout
+            )]
         );
-        assert!(infos.iter().all(|info| info.is_user_window));
     }
 
     #[test]
-    fn reopen_sequences_never_promote_secondary_labels_to_primary_main() {
-        let infos = classify_window_labels(["main", "main-1", "main-2", "main-1", "main-3"]);
+    fn ready_prepared_windows_expire_only_after_ttl() {
+        let registry = WindowRegistry::default();
+        registry.reserve_prepared_window(WindowName::Main, "main-prewarm".to_string());
+        assert!(registry.mark_prepared_window_ready(WindowName::Main, "main-prewarm"));
 
-        assert_eq!(
-            infos.iter().filter(|info| info.is_primary_window).count(),
-            1
-        );
         assert!(
-            infos
-                .iter()
-                .filter(|info| info.label != "main")
-                .all(|info| !info.is_primary_window && info.is_user_window)
+            registry
+                .stale_prepared_windows(Instant::now() + PREPARED_WINDOW_READY_TIMEOUT)
+                .is_empty()
+        );
+        assert_eq!(
+            registry.stale_prepared_windows(Instant::now() + PREPARED_WINDOW_TTL),
+            vec![(
+                WindowName::Main,
+                "main-prewarm".to_string(),
+                PreparedWindowRecycleReason::Expired
+            )]
         );
     }
 
     #[test]
-    fn authoritative_label_classification_stays_stable_across_known_cases() {
-        let cases = [
-            ("main", Some(WindowName::Main), true, true),
-            ("main-1", Some(WindowName::Main), false, true),
-            ("main-99", Some(WindowName::Main), false, true),
-            ("main-prewarm-1", Some(WindowName::Main), false, false),
-            ("support-main", None, false, false),
-            ("prewarm-main", None, false, false),
-            ("unknown", None, false, false),
-        ];
+    fn only_ready_prepared_windows_treat_page_loads_as_reloads() {
+        let registry = WindowRegistry::default();
+        registry.reserve_prepared_window(WindowName::Main, "main-prewarm".to_string());
 
-        for (label, expected_window, expected_primary, expected_user) in cases {
-            let identity = classify_window_identity(label);
+        assert_eq!(registry.ready_prepared_window_target("main-prewarm"), None);
 
-            assert_eq!(
-                identity.window, expected_window,
-                "wrong window kind for {label}"
-            );
-            assert_eq!(
-                identity.is_primary_window, expected_primary,
-                "wrong primary classification for {label}"
-            );
-            assert_eq!(
-                identity.is_user_window, expected_user,
-                "wrong user-window classification for {label}"
-            );
-        }
+        assert!(registry.mark_prepared_window_ready(WindowName::Main, "main-prewarm"));
+        assert_eq!(
+            registry.ready_prepared_window_target("main-prewarm"),
+            Some(WindowName::Main)
+        );
     }
 
     #[test]
-    fn support_and_prewarm_labels_never_classify_as_user_windows_in_mixed_enumeration() {
-        let infos = classify_window_labels([
-            "main",
-            "main-prewarm-1",
-            "support-main",
-            "prewarm-main",
-            "main-1",
-        ]);
+    fn registry_snapshot_reports_windows_inventory_owners_and_shutdown() {
+        let registry = WindowRegistry::default();
+
+        registry.reserve_prepared_window(WindowName::Main, "main-prewarm".to_string());
+        assert!(registry.mark_prepared_window_ready(WindowName::Main, "main-prewarm"));
+        assert!(registry.add_warm_window_owner(WindowName::Main, "main-1"));
+        assert!(registry.add_warm_window_owner(WindowName::Main, "main"));
+        assert!(registry.start_graceful_shutdown_tracking(["main".to_string()]));
+
+        let snapshot = registry.snapshot(
+            [
+                ("main-prewarm".to_string(), false),
+                ("main".to_string(), true),
+            ],
+            Instant::now(),
+        );
 
-        let visible_user_labels = infos
+        let windows = snapshot
+            .windows
             .iter()
-            .filter(|info| info.is_user_window)
-            .map(|info| info.label.as_str())
+            .map(|window| {
+                (
+                    window.kind.label.as_str(),
+                    window.kind.is_user_window,
+                    window.kind.is_prepared_window,
+                    window.visible,
+                )
+            })
             .collect::<Vec<_>>();
-
-        assert_eq!(visible_user_labels, vec!["main", "main-1"]);
+        assert_eq!(
+            windows,
+            vec![
+                ("main", true, false, true),
+                ("main-prewarm", false, true, false)
+            ]
+        );
+        assert_eq!(
+            snapshot
+                .prepared_windows
+                .iter()
+                .map(|slot| (slot.window, slot.label.as_str(), slot.readiness))
+                .collect::<Vec<_>>(),
+            vec![(
+                WindowName::Main,
+                "main-prewarm",
+                PreparedWindowReadiness::Ready
+            )]
+        );
+        assert_eq!(snapshot.warm_window_owners.len(), 1);
+        assert_eq!(
+            snapshot.warm_window_owners[0].owners,
+            vec!["main".to_string(), "main-1".to_string()]
+        );
+        assert_eq!(
+            snapshot
+                .graceful_shutdown
+                .map(|progress| progress.pending_labels),
+            Some(vec!["main".to_string()])
+        );
     }
+}
 
-    #[test]
-    fn closing_one_of_multiple_user_windows_does_not_exit() {
-        reset_graceful_shutdown_state();
-        assert!(!should_exit_on_window_close_with_count("main-1", 2));
-        assert!(!should_exit_on_window_close_with_count("main", 3));
-    }
+/// Drives the command surface through IPC on Tauri's mock runtime. The mock
+/// never delivers window events, so close and destroy handling is invoked
+/// directly, and closed windows stay listed by the app handle.
+#[cfg(test)]
+mod mock_runtime_tests {
+    use super::{
+        PreparedWindowReadiness, PrewarmPoolMetrics, WindowCloseAction, WindowLaunchPayload,
+        WindowName, WindowRegistry, begin_graceful_shutdown, handle_close_requested,
+        handle_window_destroyed, should_keep_running_without_windows, stream_position_updates,
+        sync_window_state, window_close_action,
+    };
+    use crate::utils::event::{WindowLifecycleEvent, WindowStateEvent};
+    use crate::utils::session::{SessionCache, capture_session, capture_session_windows};
+    use crate::utils::shutdown::ShutdownHooks;
+    use crate::utils::window_bus::{
+        self, WindowMessageEvent, WindowMessagePayload, WindowMessages,
+    };
+    use crate::utils::window_state::{
+        WindowGeometryCache, WindowStateRestore, flush_window_state, restore_window_state,
+    };
+    use serde_json::{Value, json};
+    use std::sync::{Arc, Mutex};
+    use std::time::{Duration, Instant};
+    use tauri::ipc::{CallbackFn, Channel, InvokeBody};
+    use tauri::test::{
+        INVOKE_KEY, MockRuntime, get_ipc_response, mock_builder, mock_context, noop_assets,
+    };
+    use tauri::webview::InvokeRequest;
+    use tauri::{App, Manager, WebviewUrl, WebviewWindow, WebviewWindowBuilder};
+    use tauri_specta::{Builder, Event, collect_commands, collect_events};
 
-    #[test]
-    fn closing_last_user_window_exits() {
-        reset_graceful_shutdown_state();
-        assert!(should_exit_on_window_close_with_count("main", 1));
-        assert!(should_exit_on_window_close_with_count("main-1", 1));
+    fn mock_app() -> App<MockRuntime> {
+        mock_app_with_registry(WindowRegistry::default())
     }
 
-    #[test]
-    fn reopen_close_accounting_only_exits_when_last_visible_user_window_closes() {
-        reset_graceful_shutdown_state();
-        assert!(!should_exit_on_window_close_with_count("main-2", 3));
-        assert!(!should_exit_on_window_close_with_count("main-1", 2));
-        assert!(should_exit_on_window_close_with_count("main", 1));
-        assert!(should_exit_on_window_close_with_count("main-3", 1));
+    fn mock_app_with_registry(registry: WindowRegistry) -> App<MockRuntime> {
+        let builder = Builder::<MockRuntime>::new()
+            .commands(collect_commands![
+                super::get_window_kind::<MockRuntime>,
+                super::get_window_state::<MockRuntime>,
+                super::warm_window::<MockRuntime>,
+                super::cold_window::<MockRuntime>,
+                super::prewarm_window::<MockRuntime>,
+                super::discard_prewarm_window::<MockRuntime>,
+                super::record_renderer_bootstrap_ready::<MockRuntime>,
+                super::create_window::<MockRuntime>,
+                super::take_window_launch_payload::<MockRuntime>,
+                super::set_window_session_payload::<MockRuntime>,
+                super::answer_shutdown_request::<MockRuntime>,
+                super::get_monitors::<MockRuntime>,
+                super::subscribe_mouse_and_window_position::<MockRuntime>,
+                super::unsubscribe_mouse_and_window_position::<MockRuntime>,
+                window_bus::send_window_message::<MockRuntime>,
+                window_bus::request_window_message::<MockRuntime>,
+                window_bus::reply_window_message::<MockRuntime>,
+            ])
+            .events(collect_events![
+                WindowLifecycleEvent,
+                WindowMessageEvent,
+                WindowStateEvent
+            ]);
+
+        let mut context = mock_context(noop_assets());
+        context.config_mut().identifier = "template.window-tests".to_string();
+        let app = mock_builder()
+            .manage(registry)
+            .manage(ShutdownHooks::default())
+            .manage(WindowGeometryCache::default())
+            .manage(SessionCache::default())
+            .manage(WindowMessages::default())
+            .invoke_handler(builder.invoke_handler())
+            .build(context)
+            .expect("failed to build mock app");
+        builder.mount_events(&app);
+        app
     }
 
-    #[test]
-    fn closing_support_or_prewarm_window_never_exits() {
-        reset_graceful_shutdown_state();
-        for label in ["main-prewarm-1", "support-main", "prewarm-main", "unknown"] {
-            assert!(
-                !should_exit_on_window_close_with_count(label, 1),
-                "label {label} should not participate in exit accounting"
-            );
-            assert!(
-                !should_exit_on_window_close_with_count(label, 3),
-                "label {label} should not participate in exit accounting"
-            );
-        }
+    fn open_window(app: &App<MockRuntime>, label: &str) -> WebviewWindow<MockRuntime> {
+        WebviewWindowBuilder::new(app, label, WebviewUrl::default())
+            .build()
+            .expect("failed to build mock window")
     }
 
-    #[test]
-    fn graceful_shutdown_targets_every_other_window_before_exit() {
-        let labels = graceful_shutdown_target_labels(
-            vec![
-                "main".to_string(),
-                "main-prewarm-1".to_string(),
-                "support".to_string(),
-            ],
-            "main",
-        );
+    fn window(app: &App<MockRuntime>, label: &str) -> WebviewWindow<MockRuntime> {
+        app.get_webview_window(label)
+            .unwrap_or_else(|| panic!("window {label} missing"))
+    }
 
-        assert_eq!(
-            labels,
-            vec!["main-prewarm-1".to_string(), "support".to_string()]
-        );
+    /// What the `CloseRequested` handler in `lib.rs` does for `label`; true
+    /// when the close is prevented.
+    fn request_close(app: &App<MockRuntime>, label: &str) -> bool {
+        handle_close_requested(&window(app, label).as_ref().window())
     }
 
-    #[test]
-    fn graceful_shutdown_state_allows_only_one_initiator() {
-        reset_graceful_shutdown_state();
+    fn invoke(window: &WebviewWindow<MockRuntime>, cmd: &str, args: Value) -> Value {
+        get_ipc_response(
+            window,
+            InvokeRequest {
+                cmd: cmd.into(),
+                callback: CallbackFn(0),
+                error: CallbackFn(1),
+                url: "http://tauri.localhost".parse().unwrap(),
+                body: InvokeBody::Json(args),
+                headers: Default::default(),
+                invoke_key: INVOKE_KEY.to_string(),
+            },
+        )
+        .unwrap_or_else(|error| panic!("{cmd} failed: {error}"))
+        .deserialize()
+        .expect("command response is not JSON")
+    }
 
-        assert!(try_begin_graceful_shutdown());
-        assert!(!try_begin_graceful_shutdown());
+    fn create_main_window(opener: &WebviewWindow<MockRuntime>, payload: Value) -> Value {
+        invoke(
+            opener,
+            "create_window",
+            json!({ "name": "Main", "options": null, "payload": payload }),
+        )
+    }
 
-        reset_graceful_shutdown_state();
-        assert!(try_begin_graceful_shutdown());
+    fn prepared_windows(app: &App<MockRuntime>) -> Vec<(String, PreparedWindowReadiness)> {
+        app.state::<WindowRegistry>()
+            .snapshot(Vec::new(), Instant::now())
+            .prepared_windows
+            .into_iter()
+            .map(|slot| (slot.label, slot.readiness))
+            .collect()
     }
 
-    #[test]
-    fn close_requests_stop_triggering_exit_once_graceful_shutdown_has_started() {
-        reset_graceful_shutdown_state();
-        assert!(try_begin_graceful_shutdown());
+    fn main_pool_metrics(app: &App<MockRuntime>) -> PrewarmPoolMetrics {
+        app.state::<WindowRegistry>()
+            .prewarm_pool_metrics()
+            .into_iter()
+            .find(|metrics| metrics.window == WindowName::Main)
+            .expect("main pool metrics missing")
+    }
 
-        assert!(!should_exit_on_window_close_with_count("main", 1));
-        assert!(!should_exit_on_window_close_with_count("main-1", 1));
+    /// Prepared windows are built by a spawned task, so their state settles
+    /// some time after the command that scheduled them returns.
+    fn wait_for_prepared_windows(app: &App<MockRuntime>, count: usize) {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        loop {
+            let prepared = prepared_windows(app);
+            if prepared.len() == count
+                && prepared
+                    .iter()
+                    .all(|(_, readiness)| *readiness != PreparedWindowReadiness::Scheduled)
+            {
+                return;
+            }
 
-        reset_graceful_shutdown_state();
+            assert!(
+                Instant::now() < deadline,
+                "prepared windows never settled: {prepared:?}"
+            );
+            std::thread::sleep(Duration::from_millis(10));
+        }
     }
 
     #[test]
-    fn destroyed_windows_drive_graceful_shutdown_to_completion() {
-        let _guard = test_state_guard();
-        reset_graceful_shutdown_state();
+    fn cold_create_window_opens_an_indexed_user_window_with_its_payload() {
+        let app = mock_app();
+        let main = open_window(&app, "main");
 
-        assert!(start_graceful_shutdown_tracking([
-            "main".to_string(),
-            "main-1".to_string(),
-        ]));
-        assert!(is_graceful_shutdown_in_progress());
+        let result = create_main_window(&main, json!({ "route": "/settings", "params": {} }));
+
+        assert_eq!(result["source"], json!("ColdBuild"));
+        assert_eq!(result["kind"]["label"], json!("main-1"));
+        let opened = window(&app, "main-1");
         assert_eq!(
-            graceful_shutdown_pending_labels(),
-            vec!["main".to_string(), "main-1".to_string()]
+            invoke(&opened, "get_window_kind", json!({}))["is_user_window"],
+            json!(true)
         );
-
-        assert!(!handle_destroyed_window_state("unknown"));
         assert_eq!(
-            graceful_shutdown_pending_labels(),
-            vec!["main".to_string(), "main-1".to_string()]
+            invoke(&opened, "take_window_launch_payload", json!({}))["route"],
+            json!("/settings")
         );
-
-        assert!(!handle_destroyed_window_state("main"));
         assert_eq!(
-            graceful_shutdown_pending_labels(),
-            vec!["main-1".to_string()]
+            invoke(&opened, "take_window_launch_payload", json!({})),
+            Value::Null
         );
-        assert!(is_graceful_shutdown_in_progress());
-
-        assert!(handle_destroyed_window_state("main-1"));
-        assert!(graceful_shutdown_pending_labels().is_empty());
-        assert!(!is_graceful_shutdown_in_progress());
+        assert_eq!(main_pool_metrics(&app).cold_opens, 1);
+        assert!(prepared_windows(&app).is_empty());
     }
 
     #[test]
-    fn destroyed_prepared_window_is_removed_from_inventory() {
-        let _guard = test_state_guard();
-        reset_prepared_window_inventory();
-        reset_graceful_shutdown_state();
+    fn opening_a_singleton_kind_again_focuses_it_and_forwards_the_payload() {
+        let app = mock_app();
+        let main = open_window(&app, "main");
+        let other_main = open_window(&app, "main-1");
+        let create_settings_window = |opener: &WebviewWindow<MockRuntime>, payload: Value| {
+            invoke(
+                opener,
+                "create_window",
+                json!({ "name": "Settings", "options": null, "payload": payload }),
+            )
+        };
+
+        let result = create_settings_window(&main, json!({ "route": "/about", "params": {} }));
+        assert_eq!(result["source"], json!("ColdBuild"));
+        let settings = window(&app, "settings");
+        let forwarded = Arc::new(Mutex::new(Vec::new()));
+        let received = forwarded.clone();
+        WindowMessageEvent::listen(&settings, move |event| {
+            received
+                .lock()
+                .expect("forwarded payloads poisoned")
+                .push(event.payload.payload);
+        });
 
-        reserve_prepared_window(WindowName::Main, "main-prewarm".to_string());
+        let result =
+            create_settings_window(&other_main, json!({ "route": "/licenses", "params": {} }));
 
-        assert!(!handle_destroyed_window_state("main-prewarm"));
-        assert!(prepared_window_targets().is_empty());
-        assert_eq!(prepared_window_label(WindowName::Main), None);
+        assert_eq!(result["source"], json!("Existing"));
+        assert_eq!(result["kind"]["label"], json!("settings"));
+        assert_eq!(result["kind"]["owner_label"], Value::Null);
+        assert!(app.get_webview_window("settings-1").is_none());
+        assert_eq!(
+            invoke(&settings, "take_window_launch_payload", json!({}))["route"],
+            json!("/about")
+        );
+        assert_eq!(
+            *forwarded.lock().expect("forwarded payloads poisoned"),
+            vec![WindowMessagePayload::Launch(WindowLaunchPayload {
+                route: Some("/licenses".to_string()),
+                params: Default::default(),
+            })]
+        );
     }
 
     #[test]
-    fn destroyed_promoted_window_label_is_demoted_from_user_window_state() {
-        let _guard = test_state_guard();
-        reset_prepared_window_inventory();
-        reset_graceful_shutdown_state();
-
-        promote_window_label_to_user_window("main-prewarm");
-        assert!(should_label_resolve_as_user_window("main-prewarm"));
+    fn window_requests_resolve_with_the_reply_of_their_recipient() {
+        let app = mock_app();
+        let main = open_window(&app, "main");
+        let other_main = open_window(&app, "main-1");
+        let (delivered, received) = std::sync::mpsc::channel();
+        WindowMessageEvent::listen(&other_main, move |event| {
+            let _ = delivered.send(event.payload.id);
+        });
 
-        assert!(!handle_destroyed_window_state("main-prewarm"));
-        assert!(!should_label_resolve_as_user_window("main-prewarm"));
+        let requester = main.clone();
+        let request = std::thread::spawn(move || {
+            invoke(
+                &requester,
+                "request_window_message",
+                json!({
+                    "target": { "kind": "Label", "value": "main-1" },
+                    "payload": { "type": "Ping" },
+                    "timeoutMs": 5_000,
+                }),
+            )
+        });
+        let id = received
+            .recv_timeout(Duration::from_secs(5))
+            .expect("request never reached its recipient");
+        let reply = |window: &WebviewWindow<MockRuntime>| {
+            invoke(
+                window,
+                "reply_window_message",
+                json!({ "id": id, "payload": { "type": "Pong" } }),
+            )
+        };
+
+        assert_eq!(reply(&main), json!(false));
+        assert_eq!(reply(&other_main), json!(true));
+        assert_eq!(reply(&other_main), json!(false));
+        assert_eq!(
+            request.join().expect("request thread panicked"),
+            json!({ "type": "Pong" })
+        );
     }
 
     #[test]
-    fn typed_prewarm_targets_are_keyed_by_window_enum() {
-        let _guard = test_state_guard();
-        reset_prepared_window_inventory();
+    fn position_updates_are_pushed_only_on_change_until_the_window_is_destroyed() {
+        let app = mock_app();
+        let main = open_window(&app, "main");
+        let pushed = Arc::new(Mutex::new(Vec::new()));
+        let received = pushed.clone();
+        let channel = Channel::new(move |body| {
+            received.lock().expect("pushed updates poisoned").push(body);
+            Ok(())
+        });
 
-        reserve_prepared_window(WindowName::Main, "main-prewarm".to_string());
+        let subscription_id = app
+            .state::<WindowRegistry>()
+            .start_position_subscription("main");
+        let stream = tauri::async_runtime::spawn(stream_position_updates(
+            main.clone(),
+            channel,
+            subscription_id,
+            Duration::from_millis(5),
+        ));
+        std::thread::sleep(Duration::from_millis(50));
+        handle_window_destroyed(app.handle(), "main");
+        tauri::async_runtime::block_on(stream).expect("position stream panicked");
 
-        assert_eq!(prepared_window_targets(), vec![WindowName::Main]);
+        // Neither the mock pointer nor the mock window ever moves.
+        assert_eq!(pushed.lock().expect("pushed updates poisoned").len(), 1);
+        assert_eq!(
+            invoke(&main, "unsubscribe_mouse_and_window_position", json!({})),
+            json!(false)
+        );
+    }
 
-        reserve_prepared_window(WindowName::Main, "main-prewarm".to_string());
+    #[test]
+    fn window_state_changes_are_sent_once_to_the_window_they_describe() {
+        let app = mock_app();
+        let main = open_window(&app, "main");
+        let other = open_window(&app, "main-1");
+        let main_states = Arc::new(Mutex::new(Vec::new()));
+        let received = main_states.clone();
+        WindowStateEvent::listen(&main, move |event| {
+            received
+                .lock()
+                .expect("window states poisoned")
+                .push(event.payload);
+        });
+        let other_states = Arc::new(Mutex::new(Vec::new()));
+        let received = other_states.clone();
+        WindowStateEvent::listen(&other, move |event| {
+            received
+                .lock()
+                .expect("window states poisoned")
+                .push(event.payload);
+        });
 
-        assert_eq!(prepared_window_targets(), vec![WindowName::Main]);
+        assert_eq!(
+            invoke(&main, "get_window_state", json!({})),
+            json!({
+                "is_fullscreen": false,
+                "is_maximized": false,
+                "is_minimized": false,
+                "is_focused": false,
+            })
+        );
+        // Nothing changed since the renderer read its initial state.
+        sync_window_state(&main, None);
+        sync_window_state(&main, Some(true));
+        sync_window_state(&main, Some(true));
+
+        let main_states = main_states.lock().expect("window states poisoned");
+        assert_eq!(main_states.len(), 1);
+        assert!(main_states[0].is_focused);
+        assert!(
+            other_states
+                .lock()
+                .expect("window states poisoned")
+                .is_empty()
+        );
     }
 
     #[test]
-    fn authoritative_discard_uses_descriptor_prewarm_label_for_support_windows() {
-        let _guard = test_state_guard();
-        reset_prepared_window_inventory();
+    fn the_session_records_restorable_windows_with_their_latest_payload() {
+        let app = mock_app();
+        let main = open_window(&app, "main");
+        let _support = open_window(&app, "support");
+        let opened = create_main_window(&main, json!({ "route": "/notes", "params": {} }));
+        let opened_label = opened["kind"]["label"]
+            .as_str()
+            .expect("opened window has no label")
+            .to_string();
+        invoke(
+            &main,
+            "set_window_session_payload",
+            json!({ "payload": { "route": "/inbox", "params": { "id": "3" } } }),
+        );
 
+        // No kind restores its session by default, so the test opts Main in.
+        assert!(capture_session(app.handle()).is_empty());
+        let session = capture_session_windows(app.handle(), |name| name == WindowName::Main);
+
+        let mut expected_labels = vec!["main".to_string(), opened_label];
+        expected_labels.sort();
         assert_eq!(
-            discard_prepared_window_state(WindowName::Support),
-            Some(PreparedWindowDisposition {
-                label: "support-prewarm".to_string(),
-                removed_from_inventory: false,
-            })
+            session
+                .iter()
+                .map(|window| window.label.clone())
+                .collect::<Vec<_>>(),
+            expected_labels
         );
+        let route_of = |label: &str| {
+            session
+                .iter()
+                .find(|window| window.label == label)
+                .and_then(|window| window.route.clone())
+        };
+        assert_eq!(route_of("main"), Some("/inbox".to_string()));
+        assert_eq!(route_of(&expected_labels[1]), Some("/notes".to_string()));
     }
 
     #[test]
-    fn discarded_prepared_targets_are_removed_from_inventory() {
-        let _guard = test_state_guard();
-        reset_prepared_window_inventory();
-        reserve_prepared_window(WindowName::Main, "main-prewarm".to_string());
+    fn placement_on_a_missing_monitor_keeps_the_default_position() {
+        let app = mock_app();
+        let main = open_window(&app, "main");
+
+        // The mock runtime reports no monitors at all.
+        assert_eq!(invoke(&main, "get_monitors", json!({})), json!([]));
+        invoke(
+            &main,
+            "create_window",
+            json!({
+                "name": "Main",
+                "options": {
+                    "width": null,
+                    "height": null,
+                    "placement": { "kind": "MonitorIndex", "value": 1 },
+                },
+                "payload": null,
+            }),
+        );
 
-        assert!(discard_prepared_window(WindowName::Main));
-        assert!(prepared_window_targets().is_empty());
+        assert!(app.get_webview_window("main-1").is_some());
     }
 
     #[test]
-    fn authoritative_discard_returns_existing_inventory_label() {
-        let _guard = test_state_guard();
-        reset_prepared_window_inventory();
-        reserve_prepared_window(WindowName::Main, "main-prewarm".to_string());
+    fn closing_a_window_only_exits_once_no_other_user_window_is_visible() {
+        let app = mock_app();
+        let main = open_window(&app, "main");
+        let _support = open_window(&app, "support");
 
         assert_eq!(
-            discard_prepared_window_state(WindowName::Main),
-            Some(PreparedWindowDisposition {
-                label: "main-prewarm".to_string(),
-                removed_from_inventory: true,
-            })
+            window_close_action(app.handle(), "main"),
+            WindowCloseAction::Exit
+        );
+        assert_eq!(
+            window_close_action(app.handle(), "support"),
+            WindowCloseAction::Close
         );
-        assert!(prepared_window_targets().is_empty());
-    }
 
-    #[test]
-    fn authoritative_discard_targets_canonical_label_even_without_inventory_entry() {
-        let _guard = test_state_guard();
-        reset_prepared_window_inventory();
+        create_main_window(&main, Value::Null);
 
         assert_eq!(
-            discard_prepared_window_state(WindowName::Main),
-            Some(PreparedWindowDisposition {
-                label: "main-prewarm".to_string(),
-                removed_from_inventory: false,
-            })
+            window_close_action(app.handle(), "main"),
+            WindowCloseAction::Close
+        );
+        assert_eq!(
+            window_close_action(app.handle(), "main-1"),
+            WindowCloseAction::Close
         );
     }
 
     #[test]
-    fn discarded_prepared_targets_stay_absent_during_future_open_flow() {
-        let _guard = test_state_guard();
-        reset_prepared_window_inventory();
-        reserve_prepared_window(WindowName::Main, "main-prewarm".to_string());
+    fn close_requests_are_answered_with_the_policy_of_the_window_kind() {
+        let app = mock_app();
+        let main = open_window(&app, "main");
+        invoke(
+            &main,
+            "create_window",
+            json!({ "name": "Settings", "options": null, "payload": null }),
+        );
+        create_main_window(&main, Value::Null);
+        let registry = app.state::<WindowRegistry>();
+        let (hidden, received) = std::sync::mpsc::channel();
+        WindowLifecycleEvent::listen(app.handle(), move |event| {
+            if let WindowLifecycleEvent::VisibilityChanged { label, visible, .. } = event.payload {
+                let _ = hidden.send((label, visible));
+            }
+        });
 
-        assert!(discard_prepared_window(WindowName::Main));
+        assert_eq!(
+            registry.window_kind_info_for_label("settings").owner_label,
+            None
+        );
+        assert!(request_close(&app, "settings"));
+        assert_eq!(
+            received.recv_timeout(Duration::from_secs(5)),
+            Ok(("settings".to_string(), false))
+        );
+        assert!(!request_close(&app, "main-1"));
+        assert!(!registry.is_graceful_shutdown_in_progress());
 
-        let consumed_prepared_target = take_prepared_window(WindowName::Main);
+        let app = mock_app();
+        let _main = open_window(&app, "main");
+        let registry = app.state::<WindowRegistry>();
 
-        assert!(consumed_prepared_target.is_none());
-        assert!(prepared_window_targets().is_empty());
+        assert!(request_close(&app, "main"));
+        assert_eq!(registry.graceful_shutdown_awaiting_labels(), vec!["main"]);
     }
 
-    #[test]
-    fn prewarm_creates_real_hidden_backend_window_state() {
-        let _guard = test_state_guard();
-        reset_prepared_window_inventory();
-
-        reserve_prepared_window(WindowName::Main, "main-prewarm".to_string());
+    fn answer_shutdown_request(window: &WebviewWindow<MockRuntime>, verdict: &str) {
+        let request_id = window
+            .state::<WindowRegistry>()
+            .lock()
+            .last_shutdown_request_id;
+        invoke(
+            window,
+            "answer_shutdown_request",
+            json!({ "requestId": request_id, "verdict": verdict }),
+        );
+    }
 
-        let prepared = prepared_window_targets();
-        assert_eq!(prepared, vec![WindowName::Main]);
+    #[test]
+    fn graceful_shutdown_waits_for_every_window_to_be_destroyed() {
+        let app = mock_app();
+        let main = open_window(&app, "main");
+        let _support = open_window(&app, "support");
+        create_main_window(&main, Value::Null);
+        let registry = app.state::<WindowRegistry>();
+
+        begin_graceful_shutdown(app.handle(), Some("main-1"));
         assert_eq!(
-            prepared_window_label(WindowName::Main).as_deref(),
-            Some("main-prewarm")
+            registry.graceful_shutdown_awaiting_labels(),
+            vec!["main", "main-1"]
         );
+        assert!(registry.graceful_shutdown_pending_labels().is_empty());
+
+        answer_shutdown_request(&main, "Proceed");
+        answer_shutdown_request(&window(&app, "main-1"), "Proceed");
+        assert!(!request_close(&app, "main"));
+        assert!(!request_close(&app, "support"));
+        assert!(!should_keep_running_without_windows(app.handle()));
         assert_eq!(
-            prepared_window_readiness(WindowName::Main),
-            Some(PreparedWindowReadiness::Created)
+            registry.graceful_shutdown_pending_labels(),
+            vec!["main", "main-1", "support"]
         );
-        assert!(take_prepared_window(WindowName::Main).is_none());
-        assert_eq!(prepared_window_targets(), vec![WindowName::Main]);
+
+        // The mock runtime cannot exit, so the hook never finishes and the
+        // exit it would be followed by is never requested.
+        let (hook_started, hook_started_rx) = std::sync::mpsc::channel();
+        app.state::<ShutdownHooks>().register(
+            "test",
+            0,
+            Duration::from_secs(3600),
+            move || async move {
+                let _ = hook_started.send(());
+                std::future::pending::<anyhow::Result<()>>().await
+            },
+        );
+
+        handle_window_destroyed(app.handle(), "support");
+        handle_window_destroyed(app.handle(), "main-1");
+        assert_eq!(registry.graceful_shutdown_pending_labels(), vec!["main"]);
+        assert!(!app.state::<ShutdownHooks>().is_running());
+
+        handle_window_destroyed(app.handle(), "main");
+        assert!(!registry.is_graceful_shutdown_in_progress());
+        assert!(app.state::<ShutdownHooks>().is_running());
+        hook_started_rx
+            .recv_timeout(Duration::from_secs(5))
+            .expect("shutdown hooks never ran");
     }
 
     #[test]
-    fn taking_prepared_window_consumes_authoritative_backend_state_once() {
-        let _guard = test_state_guard();
-        reset_prepared_window_inventory();
-        reserve_prepared_window(WindowName::Main, "main-prewarm".to_string());
+    fn a_vetoed_shutdown_keeps_every_window_open() {
+        let app = mock_app();
+        let main = open_window(&app, "main");
+        create_main_window(&main, Value::Null);
+        let registry = app.state::<WindowRegistry>();
 
-        assert!(mark_prepared_window_ready(WindowName::Main, "main-prewarm"));
+        begin_graceful_shutdown(app.handle(), None);
+        answer_shutdown_request(&main, "Veto");
 
-        assert!(take_prepared_window(WindowName::Main).is_some());
-        assert!(take_prepared_window(WindowName::Main).is_none());
+        assert!(!registry.is_graceful_shutdown_in_progress());
+        assert_eq!(
+            window_close_action(app.handle(), "main"),
+            WindowCloseAction::Close
+        );
+        assert!(app.get_webview_window("main-1").is_some());
     }
 
     #[test]
-    fn merely_created_hidden_window_does_not_count_as_consumable_prewarm_inventory() {
-        let _guard = test_state_guard();
-        reset_prepared_window_inventory();
-        reserve_prepared_window(WindowName::Main, "main-prewarm".to_string());
+    fn silent_windows_do_not_block_shutdown_past_the_verdict_timeout() {
+        let app = mock_app_with_registry(
+            WindowRegistry::default().shutdown_verdict_timeout(Duration::from_millis(20)),
+        );
+        let _main = open_window(&app, "main");
+        let _support = open_window(&app, "support");
+        let registry = app.state::<WindowRegistry>();
 
+        begin_graceful_shutdown(app.handle(), None);
+        assert_eq!(registry.graceful_shutdown_awaiting_labels(), vec!["main"]);
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while registry.graceful_shutdown_pending_labels().is_empty() {
+            assert!(
+                Instant::now() < deadline,
+                "shutdown never moved past the veto round"
+            );
+            std::thread::sleep(Duration::from_millis(10));
+        }
         assert_eq!(
-            prepared_window_readiness(WindowName::Main),
-            Some(PreparedWindowReadiness::Created)
+            registry.graceful_shutdown_pending_labels(),
+            vec!["main", "support"]
         );
-        assert!(take_prepared_window(WindowName::Main).is_none());
-        assert_eq!(prepared_window_targets(), vec![WindowName::Main]);
     }
 
     #[test]
-    fn ready_hidden_window_becomes_consumable_for_next_visible_open_flow() {
-        let _guard = test_state_guard();
-        reset_prepared_window_inventory();
-        reserve_prepared_window(WindowName::Main, "main-prewarm".to_string());
+    fn warm_window_fills_the_pool_and_create_window_promotes_a_ready_slot() {
+        let app = mock_app();
+        let main = open_window(&app, "main");
 
-        assert!(mark_prepared_window_ready(WindowName::Main, "main-prewarm"));
+        invoke(&main, "warm_window", json!({ "name": "Main" }));
+        wait_for_prepared_windows(&app, 2);
         assert_eq!(
-            prepared_window_readiness(WindowName::Main),
-            Some(PreparedWindowReadiness::Ready)
+            prepared_windows(&app),
+            vec![
+                ("main-prewarm".to_string(), PreparedWindowReadiness::Created),
+                (
+                    "main-prewarm-1".to_string(),
+                    PreparedWindowReadiness::Created
+                ),
+            ]
         );
 
-        let consumed = take_prepared_window(WindowName::Main);
+        let prepared = window(&app, "main-prewarm");
         assert_eq!(
-            consumed,
-            Some(PreparedWindowState {
-                label: "main-prewarm".to_string(),
-                readiness: PreparedWindowReadiness::Ready,
-            })
+            invoke(&prepared, "get_window_kind", json!({}))["is_prepared_window"],
+            json!(true)
         );
-        assert!(prepared_window_targets().is_empty());
-    }
+        invoke(&prepared, "record_renderer_bootstrap_ready", json!({}));
 
-    #[test]
-    fn readiness_updates_only_for_matching_hidden_inventory_label() {
-        let _guard = test_state_guard();
-        reset_prepared_window_inventory();
-        reserve_prepared_window(WindowName::Main, "main-prewarm".to_string());
+        let result = create_main_window(&main, json!({ "route": "/inbox", "params": {} }));
 
-        assert!(!mark_prepared_window_ready(WindowName::Main, "main-1"));
+        assert_eq!(result["source"], json!("Prewarmed"));
+        assert_eq!(result["kind"]["label"], json!("main-prewarm"));
+        let kind = invoke(&prepared, "get_window_kind", json!({}));
+        assert_eq!(kind["is_user_window"], json!(true));
+        assert_eq!(kind["is_prepared_window"], json!(false));
         assert_eq!(
-            prepared_window_readiness(WindowName::Main),
-            Some(PreparedWindowReadiness::Created)
+            invoke(&prepared, "take_window_launch_payload", json!({}))["route"],
+            json!("/inbox")
+        );
+        assert_eq!(main_pool_metrics(&app).pool_hits, 1);
+        assert_eq!(main_pool_metrics(&app).cold_opens, 0);
+
+        // The warm owner keeps the pool topped up after the promotion.
+        wait_for_prepared_windows(&app, 2);
+        assert!(
+            prepared_windows(&app)
+                .iter()
+                .all(|(label, _)| label != "main-prewarm")
         );
     }
 
     #[test]
-    fn renderer_bootstrap_ready_makes_matching_hidden_window_consumable() {
-        let _guard = test_state_guard();
-        reset_prepared_window_inventory();
-        reserve_prepared_window(WindowName::Main, "main-prewarm".to_string());
-
-        assert!(mark_prepared_window_ready(WindowName::Main, "main-prewarm"));
+    fn a_promoted_prewarm_window_does_not_pass_its_geometry_to_the_next_one() {
+        let app = mock_app();
+        let main = open_window(&app, "main");
+        invoke(&main, "warm_window", json!({ "name": "Main" }));
+        wait_for_prepared_windows(&app, 2);
+        let promoted = window(&app, "main-prewarm");
+        invoke(&promoted, "record_renderer_bootstrap_ready", json!({}));
+        let result = create_main_window(&main, Value::Null);
+        assert_eq!(result["kind"]["label"], json!("main-prewarm"));
+
+        // The mock window never moves, so the save made on close stands in
+        // for the one after a move.
+        flush_window_state(&promoted);
+        handle_window_destroyed(app.handle(), "main-prewarm");
+
+        // The mock runtime keeps destroyed windows registered, so the next
+        // window built under the label is reserved and promoted by hand.
+        let registry = app.state::<WindowRegistry>();
+        assert!(registry.reserve_prepared_window(WindowName::Main, "main-prewarm".to_string()));
+        assert!(registry.promote_window_label_to_user_window("main-prewarm"));
         assert_eq!(
-            prepared_window_readiness(WindowName::Main),
-            Some(PreparedWindowReadiness::Ready)
+            restore_window_state(app.handle(), &promoted),
+            WindowStateRestore::NotSaved
         );
     }
 
     #[test]
-    fn consumed_prewarm_label_can_be_promoted_to_user_window() {
-        let _guard = test_state_guard();
-        reset_prepared_window_inventory();
-
-        assert!(!should_label_resolve_as_user_window("main-prewarm"));
-        promote_window_label_to_user_window("main-prewarm");
-        assert!(should_label_resolve_as_user_window("main-prewarm"));
-
-        demote_window_label_from_user_window("main-prewarm");
-        assert!(!should_label_resolve_as_user_window("main-prewarm"));
+    fn create_window_falls_back_to_a_cold_open_until_a_slot_is_ready() {
+        let app = mock_app();
+        let main = open_window(&app, "main");
+
+        invoke(&main, "warm_window", json!({ "name": "Main" }));
+        wait_for_prepared_windows(&app, 2);
+        create_main_window(&main, Value::Null);
+
+        assert!(app.get_webview_window("main-1").is_some());
+        assert_eq!(main_pool_metrics(&app).pool_hits, 0);
+        assert_eq!(main_pool_metrics(&app).cold_opens, 1);
+        assert_eq!(prepared_windows(&app).len(), 2);
     }
 
     #[test]
-    fn app_ready_activation_skips_hidden_prewarm_labels() {
-        assert!(should_activate_window_on_app_ready("main"));
-        assert!(should_activate_window_on_app_ready("main-1"));
-        assert!(!should_activate_window_on_app_ready("main-prewarm"));
+    fn cold_window_from_the_last_owner_discards_the_pool() {
+        let app = mock_app();
+        let main = open_window(&app, "main");
+
+        invoke(&main, "warm_window", json!({ "name": "Main" }));
+        wait_for_prepared_windows(&app, 2);
+
+        assert_eq!(
+            invoke(&main, "cold_window", json!({ "name": "Main" })),
+            json!(true)
+        );
+        assert!(prepared_windows(&app).is_empty());
+        assert!(
+            !app.state::<WindowRegistry>()
+                .has_warm_window_owner(WindowName::Main)
+        );
+        assert_eq!(
+            invoke(&main, "discard_prewarm_window", json!({ "name": "Main" })),
+            json!(false)
+        );
     }
 
     #[test]
-    fn inventory_backed_hidden_window_classifies_as_prepared_without_user_ownership() {
-        let _guard = test_state_guard();
-        reset_prepared_window_inventory();
-        reserve_prepared_window(WindowName::Main, "main-prewarm".to_string());
+    fn destroying_the_last_warm_owner_discards_the_pool() {
+        let app = mock_app();
+        let main = open_window(&app, "main");
+        create_main_window(&main, Value::Null);
+        let second = window(&app, "main-1");
 
-        let info = window_kind_info_for_label("main-prewarm");
-        assert_eq!(info.window, Some(WindowName::Main));
-        assert!(!info.is_user_window);
-        assert!(info.is_prepared_window);
+        invoke(&main, "warm_window", json!({ "name": "Main" }));
+        invoke(&second, "warm_window", json!({ "name": "Main" }));
+        wait_for_prepared_windows(&app, 2);
+
+        handle_window_destroyed(app.handle(), "main-1");
+        assert_eq!(prepared_windows(&app).len(), 2);
+
+        handle_window_destroyed(app.handle(), "main");
+        assert!(prepared_windows(&app).is_empty());
     }
 
     #[test]
-    fn consuming_hidden_window_removes_prepared_state_before_user_promotion() {
-        let _guard = test_state_guard();
-        reset_prepared_window_inventory();
-        reserve_prepared_window(WindowName::Main, "main-prewarm".to_string());
-        assert!(mark_prepared_window_ready(WindowName::Main, "main-prewarm"));
+    fn destroyed_prepared_window_is_replaced_while_warm() {
+        let app = mock_app();
+        let main = open_window(&app, "main");
 
-        let consumed = take_prepared_window(WindowName::Main);
-        assert!(consumed.is_some());
+        invoke(&main, "warm_window", json!({ "name": "Main" }));
+        wait_for_prepared_windows(&app, 2);
 
-        let before_promotion = window_kind_info_for_label("main-prewarm");
-        assert!(!before_promotion.is_user_window);
-        assert!(!before_promotion.is_prepared_window);
+        let _ = window(&app, "main-prewarm").close();
+        handle_window_destroyed(app.handle(), "main-prewarm");
 
-        promote_window_label_to_user_window("main-prewarm");
-        let after_promotion = window_kind_info_for_label("main-prewarm");
-        assert!(after_promotion.is_user_window);
-        assert!(!after_promotion.is_prepared_window);
+        wait_for_prepared_windows(&app, 2);
+        assert!(
+            prepared_windows(&app)
+                .iter()
+                .all(|(label, _)| label != "main-prewarm")
+        );
     }
 }
@@ -0,0 +1,350 @@
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Mutex, MutexGuard};
+use std::time::Duration;
+use tauri::{AppHandle, Manager, Runtime, WebviewWindow};
+use tauri_specta::Event;
+use tokio::sync::oneshot;
+
+use super::error::AppError;
+use super::window::{WindowLaunchPayload, WindowName, WindowRegistry, window_kind_from_label};
+
+const DEFAULT_WINDOW_REQUEST_TIMEOUT_MS: u32 = 5_000;
+/// Sender label of messages that come from outside the app, such as a deep
+/// link handed to a window that is already open.
+const LAUNCHER_MESSAGE_SENDER: &str = "launcher";
+
+#[derive(Serialize, Deserialize, Type, Clone, Debug, PartialEq, Eq)]
+#[serde(tag = "kind", content = "value")]
+pub enum WindowMessageTarget {
+    Label(String),
+    Window(WindowName),
+    Broadcast,
+}
+
+#[derive(Serialize, Deserialize, Type, Clone, Debug, PartialEq, Eq)]
+#[serde(tag = "type", content = "data")]
+pub enum WindowMessagePayload {
+    Ping,
+    Pong,
+    Navigate { route: String },
+    Launch(WindowLaunchPayload),
+}
+
+#[derive(Serialize, Deserialize, Type, Clone, Debug, Event)]
+pub struct WindowMessageEvent {
+    pub id: u32,
+    pub from: String,
+    pub expects_reply: bool,
+    pub payload: WindowMessagePayload,
+}
+
+/// A request waiting for its reply, which only the windows it was sent to may
+/// give.
+#[derive(Debug)]
+struct PendingWindowRequest {
+    recipients: Vec<String>,
+    sender: oneshot::Sender<WindowMessagePayload>,
+}
+
+/// Message ids and the requests still waiting for a reply. It is managed as
+/// Tauri state.
+#[derive(Debug)]
+pub struct WindowMessages {
+    next_id: AtomicU32,
+    pending_requests: Mutex<HashMap<u32, PendingWindowRequest>>,
+}
+
+impl Default for WindowMessages {
+    fn default() -> Self {
+        Self {
+            next_id: AtomicU32::new(1),
+            pending_requests: Mutex::default(),
+        }
+    }
+}
+
+impl WindowMessages {
+    fn next_id(&self) -> u32 {
+        self.next_id.fetch_add(1, Ordering::Relaxed)
+    }
+
+    fn pending_requests(&self) -> MutexGuard<'_, HashMap<u32, PendingWindowRequest>> {
+        self.pending_requests
+            .lock()
+            .expect("pending window requests poisoned")
+    }
+
+    fn insert_pending_request(
+        &self,
+        id: u32,
+        recipients: Vec<String>,
+        sender: oneshot::Sender<WindowMessagePayload>,
+    ) {
+        self.pending_requests()
+            .insert(id, PendingWindowRequest { recipients, sender });
+    }
+
+    fn take_pending_request(&self, id: u32) -> Option<PendingWindowRequest> {
+        self.pending_requests().remove(&id)
+    }
+
+    /// Leaves the request pending when `replier` is not one of its recipients.
+    fn take_pending_reply(
+        &self,
+        id: u32,
+        replier: &str,
+    ) -> Option<oneshot::Sender<WindowMessagePayload>> {
+        let mut requests = self.pending_requests();
+        if !requests
+            .get(&id)?
+            .recipients
+            .iter()
+            .any(|label| label == replier)
+        {
+            return None;
+        }
+
+        requests.remove(&id).map(|request| request.sender)
+    }
+}
+
+/// Prepared windows are hidden and unowned, so they never take part in the
+/// bus; the sender only receives its own message when addressed by label.
+fn window_message_recipients(
+    registry: &WindowRegistry,
+    labels: impl IntoIterator<Item = String>,
+    sender_label: &str,
+    target: &WindowMessageTarget,
+) -> Vec<String> {
+    let mut recipients = labels
+        .into_iter()
+        .filter(|label| !registry.is_label_reserved_for_prepared_window(label))
+        .filter(|label| match target {
+            WindowMessageTarget::Label(target_label) => label == target_label,
+            WindowMessageTarget::Window(name) => {
+                label != sender_label && window_kind_from_label(label) == Some(*name)
+            }
+            WindowMessageTarget::Broadcast => label != sender_label,
+        })
+        .collect::<Vec<_>>();
+    recipients.sort();
+    recipients
+}
+
+fn resolve_window_message_recipients<R: Runtime>(
+    app: &AppHandle<R>,
+    sender: &WebviewWindow<R>,
+    target: &WindowMessageTarget,
+) -> Result<Vec<String>, AppError> {
+    let labels = app.webview_windows().keys().cloned().collect::<Vec<_>>();
+    let recipients = window_message_recipients(
+        &app.state::<WindowRegistry>(),
+        labels,
+        sender.label(),
+        target,
+    );
+    if recipients.is_empty() {
+        return Err(AppError::no_message_recipient(target));
+    }
+
+    Ok(recipients)
+}
+
+fn emit_window_message<R: Runtime>(
+    app: &AppHandle<R>,
+    recipients: &[String],
+    message: &WindowMessageEvent,
+) -> Result<(), AppError> {
+    for label in recipients {
+        message
+            .emit_to(app, label.as_str())
+            .map_err(AppError::window_message_undelivered)?;
+    }
+
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn send_window_message<R: Runtime>(
+    app: AppHandle<R>,
+    window: WebviewWindow<R>,
+    target: WindowMessageTarget,
+    payload: WindowMessagePayload,
+) -> Result<u32, AppError> {
+    let message = WindowMessageEvent {
+        id: app.state::<WindowMessages>().next_id(),
+        from: window.label().to_string(),
+        expects_reply: false,
+        payload,
+    };
+
+    let recipients = resolve_window_message_recipients(&app, &window, &target)?;
+    emit_window_message(&app, &recipients, &message)?;
+    Ok(recipients.len() as u32)
+}
+
+/// Windows that are still opening take their payload through
+/// `take_window_launch_payload`; running ones receive it as a bus message.
+pub fn send_launch_payload<R: Runtime>(
+    app: &AppHandle<R>,
+    label: &str,
+    payload: WindowLaunchPayload,
+) -> Result<(), AppError> {
+    WindowMessageEvent {
+        id: app.state::<WindowMessages>().next_id(),
+        from: LAUNCHER_MESSAGE_SENDER.to_string(),
+        expects_reply: false,
+        payload: WindowMessagePayload::Launch(payload),
+    }
+    .emit_to(app, label)
+    .map_err(AppError::window_message_undelivered)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn request_window_message<R: Runtime>(
+    app: AppHandle<R>,
+    window: WebviewWindow<R>,
+    target: WindowMessageTarget,
+    payload: WindowMessagePayload,
+    timeout_ms: Option<u32>,
+) -> Result<WindowMessagePayload, AppError> {
+    let recipients = resolve_window_message_recipients(&app, &window, &target)?;
+    let messages = app.state::<WindowMessages>();
+    let id = messages.next_id();
+    let (sender, receiver) = oneshot::channel();
+    messages.insert_pending_request(id, recipients.clone(), sender);
+
+    let message = WindowMessageEvent {
+        id,
+        from: window.label().to_string(),
+        expects_reply: true,
+        payload,
+    };
+    if let Err(error) = emit_window_message(&app, &recipients, &message) {
+        let _ = messages.take_pending_request(id);
+        return Err(error);
+    }
+
+    let timeout = Duration::from_millis(u64::from(
+        timeout_ms.unwrap_or(DEFAULT_WINDOW_REQUEST_TIMEOUT_MS),
+    ));
+    match tokio::time::timeout(timeout, receiver).await {
+        Ok(Ok(reply)) => Ok(reply),
+        Ok(Err(_)) => Err(AppError::window_request_dropped(id)),
+        Err(_) => {
+            let _ = messages.take_pending_request(id);
+            Err(AppError::window_request_timed_out(id))
+        }
+    }
+}
+
+/// The first reply wins; later replies to the same request, and replies from
+/// windows it was not sent to, are ignored.
+#[tauri::command]
+#[specta::specta]
+pub fn reply_window_message<R: Runtime>(
+    window: WebviewWindow<R>,
+    id: u32,
+    payload: WindowMessagePayload,
+) -> bool {
+    let Some(sender) = window
+        .state::<WindowMessages>()
+        .take_pending_reply(id, window.label())
+    else {
+        return false;
+    };
+
+    sender.send(payload).is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        WindowMessagePayload, WindowMessageTarget, WindowMessages, oneshot,
+        window_message_recipients,
+    };
+    use crate::utils::window::{WindowName, WindowRegistry};
+
+    fn labels() -> Vec<String> {
+        ["main", "main-1", "main-2", "support"]
+            .into_iter()
+            .map(str::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn label_target_reaches_only_that_window() {
+        assert_eq!(
+            window_message_recipients(
+                &WindowRegistry::default(),
+                labels(),
+                "main",
+                &WindowMessageTarget::Label("main-2".to_string())
+            ),
+            vec!["main-2".to_string()]
+        );
+    }
+
+    #[test]
+    fn window_kind_target_reaches_every_other_window_of_that_kind() {
+        assert_eq!(
+            window_message_recipients(
+                &WindowRegistry::default(),
+                labels(),
+                "main",
+                &WindowMessageTarget::Window(WindowName::Main)
+            ),
+            vec!["main-1".to_string(), "main-2".to_string()]
+        );
+    }
+
+    #[test]
+    fn broadcast_skips_the_sender() {
+        assert_eq!(
+            window_message_recipients(
+                &WindowRegistry::default(),
+                labels(),
+                "support",
+                &WindowMessageTarget::Broadcast
+            ),
+            vec![
+                "main".to_string(),
+                "main-1".to_string(),
+                "main-2".to_string()
+            ]
+        );
+    }
+
+    #[test]
+    fn pending_request_resolves_only_once() {
+        let messages = WindowMessages::default();
+        let (sender, mut receiver) = oneshot::channel();
+        messages.insert_pending_request(1, vec!["main-1".to_string()], sender);
+
+        let sender = messages
+            .take_pending_reply(1, "main-1")
+            .expect("pending request missing");
+        assert!(sender.send(WindowMessagePayload::Pong).is_ok());
+        assert!(messages.take_pending_reply(1, "main-1").is_none());
+        assert_eq!(receiver.try_recv(), Ok(WindowMessagePayload::Pong));
+    }
+
+    #[test]
+    fn only_recipients_can_reply_to_a_request() {
+        let messages = WindowMessages::default();
+        let (sender, _receiver) = oneshot::channel();
+        messages.insert_pending_request(
+            1,
+            vec!["main-1".to_string(), "support".to_string()],
+            sender,
+        );
+
+        assert!(messages.take_pending_reply(1, "main").is_none());
+        assert!(messages.take_pending_reply(1, "support").is_some());
+    }
+}
@@ -0,0 +1,71 @@
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::fmt;
+
+use super::window::{
+    UserWindowPolicy, WindowClosePolicy, WindowDescriptor, WindowDescriptorRegistryBuilder,
+    WindowInstancePolicy, WindowPlacement,
+};
+
+/// Window kinds known to the renderer. Adding a kind means adding a variant
+/// here and declaring its descriptor in [`window_descriptors`]; the lifecycle
+/// core in `window.rs` only consumes the registered descriptors.
+#[derive(Serialize, Deserialize, Type, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum WindowName {
+    Main,
+    Support,
+    Settings,
+}
+
+impl WindowName {
+    pub const ALL: [WindowName; 3] = [WindowName::Main, WindowName::Support, WindowName::Settings];
+
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            WindowName::Main => "main",
+            WindowName::Support => "support",
+            WindowName::Settings => "settings",
+        }
+    }
+}
+
+impl fmt::Display for WindowName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+pub fn window_descriptors() -> WindowDescriptorRegistryBuilder {
+    WindowDescriptorRegistryBuilder::default()
+        .window(
+            WindowDescriptor::new(WindowName::Main, "main")
+                .title("main")
+                .size(1400.0, 750.0)
+                .min_size(768.0, 500.0)
+                .route("index.html")
+                .launch_target("main")
+                .user_window_policy(UserWindowPolicy::PrimaryAndIndexed)
+                .placement(WindowPlacement::Cascade { step: 32 })
+                // Set to true to reopen the open main windows on the next start.
+                .restore_session(false)
+                .prewarm(true)
+                .prewarm_pool_size(2)
+                .primary_window_setup(true),
+        )
+        .window(
+            WindowDescriptor::new(WindowName::Support, "support")
+                .route("index.html")
+                .launch_target("support")
+                .owned_by_opener(true)
+                .modal(true),
+        )
+        .window(
+            WindowDescriptor::new(WindowName::Settings, "settings")
+                .title("settings")
+                .size(720.0, 560.0)
+                .min_size(480.0, 400.0)
+                .route("index.html")
+                .instance_policy(WindowInstancePolicy::Singleton)
+                .close_policy(WindowClosePolicy::HideWindow),
+        )
+}
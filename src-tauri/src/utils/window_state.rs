@@ -0,0 +1,396 @@
+use crate::domain::models::window_state::WindowState;
+use std::collections::HashMap;
+use std::sync::{Mutex, MutexGuard};
+use std::time::Duration;
+use tauri::{AppHandle, Manager, Runtime, WebviewWindow};
+use tauri::{PhysicalPosition, PhysicalSize, Position, Size};
+
+use super::window::{
+    WindowRegistry, clamp_i64_to_i32, clamp_i64_to_u32, is_window_fully_within_area,
+};
+
+const WINDOW_STATE_SAVE_DEBOUNCE: Duration = Duration::from_millis(400);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WindowGeometry {
+    pub position: PhysicalPosition<i32>,
+    pub size: PhysicalSize<u32>,
+    pub maximized: bool,
+    pub fullscreen: bool,
+    pub monitor: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WindowStateRestore {
+    Restored,
+    MonitorMissing,
+    NotSaved,
+}
+
+/// Last known geometry of each window by label, and the generation of its
+/// debounced save, so only the latest scheduled save writes. It is managed as
+/// Tauri state.
+#[derive(Debug, Default)]
+pub struct WindowGeometryCache {
+    geometries: Mutex<HashMap<String, WindowGeometry>>,
+    save_generations: Mutex<HashMap<String, u64>>,
+}
+
+impl WindowGeometryCache {
+    fn geometries(&self) -> MutexGuard<'_, HashMap<String, WindowGeometry>> {
+        self.geometries
+            .lock()
+            .expect("window geometry cache poisoned")
+    }
+
+    fn save_generations(&self) -> MutexGuard<'_, HashMap<String, u64>> {
+        self.save_generations
+            .lock()
+            .expect("window state save generations poisoned")
+    }
+
+    fn saved_geometry(&self, label: &str) -> Option<WindowGeometry> {
+        self.geometries().get(label).cloned()
+    }
+
+    fn next_save_generation(&self, label: &str) -> u64 {
+        let mut generations = self.save_generations();
+        let generation = generations.entry(label.to_string()).or_default();
+        *generation += 1;
+        *generation
+    }
+
+    fn is_latest_save_generation(&self, label: &str, generation: u64) -> bool {
+        self.save_generations()
+            .get(label)
+            .is_some_and(|latest| *latest == generation)
+    }
+}
+
+impl From<WindowState> for WindowGeometry {
+    fn from(state: WindowState) -> Self {
+        Self {
+            position: PhysicalPosition::new(clamp_i64_to_i32(state.x), clamp_i64_to_i32(state.y)),
+            size: PhysicalSize::new(
+                clamp_i64_to_u32(state.width),
+                clamp_i64_to_u32(state.height),
+            ),
+            maximized: state.maximized,
+            fullscreen: state.fullscreen,
+            monitor: state.monitor,
+        }
+    }
+}
+
+fn window_state_record(label: &str, geometry: &WindowGeometry) -> WindowState {
+    WindowState {
+        id: label.into(),
+        label: label.to_string(),
+        x: i64::from(geometry.position.x),
+        y: i64::from(geometry.position.y),
+        width: i64::from(geometry.size.width),
+        height: i64::from(geometry.size.height),
+        maximized: geometry.maximized,
+        fullscreen: geometry.fullscreen,
+        monitor: geometry.monitor.clone(),
+    }
+}
+
+pub async fn load_persisted_window_states<R: Runtime>(app: &AppHandle<R>) -> anyhow::Result<()> {
+    let states = WindowState::list().await?;
+    let geometry_cache = app.state::<WindowGeometryCache>();
+    let mut cache = geometry_cache.geometries();
+    for state in states {
+        let label = state.label.clone();
+        cache.insert(label, state.into());
+    }
+    Ok(())
+}
+
+/// Writes every cached geometry in one batch. The saves made while windows
+/// close are spawned tasks that exit can cut short, so shutdown ends with this.
+pub async fn persist_cached_window_states<R: Runtime>(app: AppHandle<R>) -> anyhow::Result<()> {
+    let records = app
+        .state::<WindowGeometryCache>()
+        .geometries()
+        .iter()
+        .map(|(label, geometry)| window_state_record(label, geometry))
+        .collect::<Vec<_>>();
+    if records.is_empty() {
+        return Ok(());
+    }
+
+    WindowState::save_many(records).await?;
+    Ok(())
+}
+
+/// Maximized and fullscreen windows report the monitor-sized frame, so the
+/// last normal bounds are kept to restore the window once it is un-maximized.
+fn merge_captured_geometry(
+    previous: Option<&WindowGeometry>,
+    captured: WindowGeometry,
+) -> WindowGeometry {
+    let Some(previous) = previous else {
+        return captured;
+    };
+
+    if !captured.maximized && !captured.fullscreen {
+        return captured;
+    }
+
+    WindowGeometry {
+        position: previous.position,
+        size: previous.size,
+        monitor: previous.monitor.clone().or(captured.monitor.clone()),
+        ..captured
+    }
+}
+
+fn capture_window_geometry<R: Runtime>(window: &WebviewWindow<R>) -> Option<WindowGeometry> {
+    let position = window.outer_position().ok()?;
+    let size = window.outer_size().ok()?;
+    let monitor = window
+        .current_monitor()
+        .ok()
+        .flatten()
+        .and_then(|monitor| monitor.name().cloned());
+
+    Some(WindowGeometry {
+        position,
+        size,
+        maximized: window.is_maximized().unwrap_or(false),
+        fullscreen: window.is_fullscreen().unwrap_or(false),
+        monitor,
+    })
+}
+
+/// Geometry `window` would be restored with if it were saved now.
+pub fn current_window_geometry<R: Runtime>(window: &WebviewWindow<R>) -> Option<WindowGeometry> {
+    let captured = capture_window_geometry(window)?;
+    let previous = window
+        .state::<WindowGeometryCache>()
+        .saved_geometry(window.label());
+    Some(merge_captured_geometry(previous.as_ref(), captured))
+}
+
+fn record_window_geometry<R: Runtime>(window: &WebviewWindow<R>) -> Option<WindowState> {
+    let label = window.label();
+    let captured = capture_window_geometry(window)?;
+    let geometry_cache = window.state::<WindowGeometryCache>();
+    let mut cache = geometry_cache.geometries();
+    let geometry = merge_captured_geometry(cache.get(label), captured);
+    if cache.get(label) == Some(&geometry) {
+        return None;
+    }
+
+    let record = window_state_record(label, &geometry);
+    cache.insert(label.to_string(), geometry);
+    Some(record)
+}
+
+async fn persist_window_state(record: WindowState) {
+    let label = record.label.clone();
+    if let Err(error) = WindowState::save_many(vec![record]).await {
+        eprintln!("Failed to persist window state for {label}: {error}");
+    }
+}
+
+pub fn schedule_window_state_save<R: Runtime>(app: &AppHandle<R>, label: &str) {
+    if !app
+        .state::<WindowRegistry>()
+        .should_remember_window_geometry(label)
+    {
+        return;
+    }
+
+    let generation = app
+        .state::<WindowGeometryCache>()
+        .next_save_generation(label);
+    let label = label.to_string();
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(WINDOW_STATE_SAVE_DEBOUNCE).await;
+        if !app
+            .state::<WindowGeometryCache>()
+            .is_latest_save_generation(&label, generation)
+        {
+            return;
+        }
+
+        let record = app
+            .get_webview_window(&label)
+            .and_then(|window| record_window_geometry(&window));
+        if let Some(record) = record {
+            persist_window_state(record).await;
+        }
+    });
+}
+
+pub fn flush_window_state<R: Runtime>(window: &WebviewWindow<R>) {
+    let label = window.label();
+    if !window
+        .state::<WindowRegistry>()
+        .should_remember_window_geometry(label)
+    {
+        return;
+    }
+
+    let _ = window
+        .state::<WindowGeometryCache>()
+        .next_save_generation(label);
+    if let Some(record) = record_window_geometry(window) {
+        tauri::async_runtime::spawn(persist_window_state(record));
+    }
+}
+
+fn is_geometry_on_monitor<'a>(
+    geometry: &WindowGeometry,
+    monitors: impl IntoIterator<Item = (Option<&'a str>, PhysicalPosition<i32>, PhysicalSize<u32>)>,
+) -> bool {
+    monitors
+        .into_iter()
+        .any(|(name, area_position, area_size)| {
+            let same_monitor = match geometry.monitor.as_deref() {
+                Some(saved_name) => name == Some(saved_name),
+                None => true,
+            };
+
+            same_monitor
+                && is_window_fully_within_area(
+                    geometry.position,
+                    geometry.size,
+                    area_position,
+                    area_size,
+                )
+        })
+}
+
+pub fn restore_window_state<R: Runtime>(
+    app: &AppHandle<R>,
+    window: &WebviewWindow<R>,
+) -> WindowStateRestore {
+    if !app
+        .state::<WindowRegistry>()
+        .should_remember_window_geometry(window.label())
+    {
+        return WindowStateRestore::NotSaved;
+    }
+
+    let Some(geometry) = app
+        .state::<WindowGeometryCache>()
+        .saved_geometry(window.label())
+    else {
+        return WindowStateRestore::NotSaved;
+    };
+
+    apply_window_geometry(app, window, &geometry)
+}
+
+/// Moves `window` to `geometry` unless that would put it off every connected
+/// monitor, in which case only the maximized state is applied.
+pub fn apply_window_geometry<R: Runtime>(
+    app: &AppHandle<R>,
+    window: &WebviewWindow<R>,
+    geometry: &WindowGeometry,
+) -> WindowStateRestore {
+    let monitors = app.available_monitors().unwrap_or_default();
+    let monitor_areas = monitors.iter().map(|monitor| {
+        let work_area = monitor.work_area();
+        (
+            monitor.name().map(String::as_str),
+            work_area.position,
+            work_area.size,
+        )
+    });
+    if !is_geometry_on_monitor(geometry, monitor_areas) {
+        if geometry.maximized {
+            let _ = window.maximize();
+        }
+        return WindowStateRestore::MonitorMissing;
+    }
+
+    let _ = window.set_size(Size::Physical(geometry.size));
+    let _ = window.set_position(Position::Physical(geometry.position));
+    if geometry.maximized {
+        let _ = window.maximize();
+    }
+    if geometry.fullscreen {
+        let _ = window.set_fullscreen(true);
+    }
+
+    WindowStateRestore::Restored
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{WindowGeometry, is_geometry_on_monitor, merge_captured_geometry};
+    use tauri::{PhysicalPosition, PhysicalSize};
+
+    fn geometry(x: i32, y: i32, monitor: Option<&str>) -> WindowGeometry {
+        WindowGeometry {
+            position: PhysicalPosition::new(x, y),
+            size: PhysicalSize::new(800, 600),
+            maximized: false,
+            fullscreen: false,
+            monitor: monitor.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn maximized_capture_keeps_previous_normal_bounds() {
+        let previous = geometry(100, 120, Some("DP-1"));
+        let captured = WindowGeometry {
+            position: PhysicalPosition::new(0, 0),
+            size: PhysicalSize::new(1920, 1080),
+            maximized: true,
+            ..geometry(0, 0, Some("DP-1"))
+        };
+
+        let merged = merge_captured_geometry(Some(&previous), captured);
+
+        assert_eq!(merged.position, PhysicalPosition::new(100, 120));
+        assert_eq!(merged.size, PhysicalSize::new(800, 600));
+        assert!(merged.maximized);
+    }
+
+    #[test]
+    fn normal_capture_replaces_previous_bounds() {
+        let previous = geometry(100, 120, Some("DP-1"));
+        let captured = geometry(300, 200, Some("DP-2"));
+
+        assert_eq!(
+            merge_captured_geometry(Some(&previous), captured.clone()),
+            captured
+        );
+    }
+
+    #[test]
+    fn saved_geometry_requires_its_monitor_to_still_be_connected() {
+        let saved = geometry(100, 120, Some("DP-2"));
+        let monitors = [(
+            Some("DP-1"),
+            PhysicalPosition::new(0, 0),
+            PhysicalSize::new(1920, 1080),
+        )];
+
+        assert!(!is_geometry_on_monitor(&saved, monitors));
+    }
+
+    #[test]
+    fn saved_geometry_must_fit_inside_the_monitor_work_area() {
+        let monitors = [(
+            Some("DP-1"),
+            PhysicalPosition::new(0, 0),
+            PhysicalSize::new(1920, 1080),
+        )];
+
+        assert!(is_geometry_on_monitor(
+            &geometry(100, 120, Some("DP-1")),
+            monitors
+        ));
+        assert!(!is_geometry_on_monitor(
+            &geometry(1500, 900, Some("DP-1")),
+            monitors
+        ));
+    }
+}
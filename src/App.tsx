@@ -52,7 +52,7 @@ function MainWindowContent() {
         )}
         type="button"
         onClick={() => {
-          void crab.createWindow("Main", null).catch((error) => {
+          void crab.createWindow("Main", null, null).catch((error) => {
             console.error("Failed to create main window", error);
           });
         }}
@@ -67,6 +67,10 @@ function SupportWindowContent() {
   return null;
 }
 
+function SettingsWindowContent() {
+  return null;
+}
+
 function MainWindowApp() {
   return (
     <Base>
@@ -83,12 +87,21 @@ function SupportWindowApp() {
   );
 }
 
+function SettingsWindowApp() {
+  return (
+    <Base>
+      <SettingsWindowContent />
+    </Base>
+  );
+}
+
 function App() {
   const app = useAppBootstrap();
 
   return app.window.match({
     main: () => <MainWindowApp />,
     support: () => <SupportWindowApp />,
+    settings: () => <SettingsWindowApp />,
   });
 }
 
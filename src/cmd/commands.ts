@@ -32,31 +32,128 @@ import * as __TAURI_EVENT from "@tauri-apps/api/event";
 
 /** Commands */
 export const commands = {
-	exists: (path: string) => typedError<boolean, string>(__TAURI_INVOKE("exists", { path })),
+	exists: (path: string) => typedError<boolean, AppError>(__TAURI_INVOKE("exists", { path })),
 	appReady: () => __TAURI_INVOKE<void>("app_ready"),
-	getMouseAndWindowPosition: () => typedError<MouseWindowInfo, string>(__TAURI_INVOKE("get_mouse_and_window_position")),
+	getMouseAndWindowPosition: () => typedError<MouseWindowInfo, AppError>(__TAURI_INVOKE("get_mouse_and_window_position")),
+	/**
+	 * Pushes `MouseWindowInfo` for the calling window over `channel`, sampled
+	 * every `interval_ms`, instead of having the renderer poll
+	 * `get_mouse_and_window_position` every frame.
+	 */
+	subscribeMouseAndWindowPosition: (channel: Channel<MouseWindowInfo>, intervalMs: number | null) => __TAURI_INVOKE<void>("subscribe_mouse_and_window_position", { channel, intervalMs }),
+	unsubscribeMouseAndWindowPosition: () => __TAURI_INVOKE<boolean>("unsubscribe_mouse_and_window_position"),
+	getMonitors: () => typedError<MonitorInfo[], AppError>(__TAURI_INVOKE("get_monitors")),
 	getWindowKind: () => __TAURI_INVOKE<WindowKindInfo>("get_window_kind"),
+	/**
+	 * Initial value for `WindowStateEvent`; later changes are only sent when they
+	 * differ from what this returned.
+	 */
+	getWindowState: () => typedError<WindowStateEvent, AppError>(__TAURI_INVOKE("get_window_state")),
 	warmWindow: (name: WindowName) => __TAURI_INVOKE<void>("warm_window", { name }),
 	coldWindow: (name: WindowName) => __TAURI_INVOKE<boolean>("cold_window", { name }),
 	prewarmWindow: (name: WindowName) => __TAURI_INVOKE<void>("prewarm_window", { name }),
 	discardPrewarmWindow: (name: WindowName) => __TAURI_INVOKE<boolean>("discard_prewarm_window", { name }),
+	getPrewarmPoolMetrics: () => __TAURI_INVOKE<PrewarmPoolMetrics[]>("get_prewarm_pool_metrics"),
+	getWindowRegistrySnapshot: () => __TAURI_INVOKE<WindowRegistrySnapshot>("get_window_registry_snapshot"),
 	recordRendererBootstrapReady: () => __TAURI_INVOKE<void>("record_renderer_bootstrap_ready"),
 	createWindow: (name: WindowName, options: {
 	width: number | null,
 	height: number | null,
-} | null) => __TAURI_INVOKE<void>("create_window", { name, options }),
-	runBunHelloSidecar: (input: string | null) => typedError<BunSidecarOutput, string>(__TAURI_INVOKE("run_bun_hello_sidecar", { input })),
-	greet: (name: string) => typedError<string, string>(__TAURI_INVOKE("greet", { name })),
-	clean: () => typedError<string, string>(__TAURI_INVOKE("clean")),
+	placement: MonitorPlacement | null,
+} | null, payload: {
+	route: string | null,
+	params: { [key in string]: string },
+} | null) => typedError<OpenedWindow, AppError>(__TAURI_INVOKE("create_window", { name, options, payload })),
+	takeWindowLaunchPayload: () => __TAURI_INVOKE<{
+	route: string | null,
+	params: { [key in string]: string },
+} | null>("take_window_launch_payload"),
+	/**
+	 * Replaces the payload the calling window reopens with when the session is
+	 * restored, so it comes back where the user left it.
+	 */
+	setWindowSessionPayload: (payload: {
+	route: string | null,
+	params: { [key in string]: string },
+} | null) => __TAURI_INVOKE<void>("set_window_session_payload", { payload }),
+	answerShutdownRequest: (requestId: number, verdict: ShutdownVerdict) => __TAURI_INVOKE<void>("answer_shutdown_request", { requestId, verdict }),
+	sendWindowMessage: (target: WindowMessageTarget, payload: WindowMessagePayload) => typedError<number, AppError>(__TAURI_INVOKE("send_window_message", { target, payload })),
+	requestWindowMessage: (target: WindowMessageTarget, payload: WindowMessagePayload, timeoutMs: number | null) => typedError<WindowMessagePayload, AppError>(__TAURI_INVOKE("request_window_message", { target, payload, timeoutMs })),
+	/**
+	 * The first reply wins; later replies to the same request, and replies from
+	 * windows it was not sent to, are ignored.
+	 */
+	replyWindowMessage: (id: number, payload: WindowMessagePayload) => __TAURI_INVOKE<boolean>("reply_window_message", { id, payload }),
+	runBunHelloSidecar: (input: string | null) => typedError<BunSidecarOutput, AppError>(__TAURI_INVOKE("run_bun_hello_sidecar", { input })),
+	greet: (name: string) => typedError<string, AppError>(__TAURI_INVOKE("greet", { name })),
+	clean: () => typedError<string, AppError>(__TAURI_INVOKE("clean")),
 };
 
 /** Events */
 export const events = {
 	//@type {ReturnType<typeof makeEvent<FullScreenEvent>>}
 	fullScreenEvent: makeEvent<FullScreenEvent>("full-screen-event"),
+	//@type {ReturnType<typeof makeEvent<WindowStateEvent>>}
+	windowStateEvent: makeEvent<WindowStateEvent>("window-state-event"),
+	//@type {ReturnType<typeof makeEvent<WindowLifecycleEvent>>}
+	windowLifecycleEvent: makeEvent<WindowLifecycleEvent>("window-lifecycle-event"),
+	//@type {ReturnType<typeof makeEvent<WindowMessageEvent>>}
+	windowMessageEvent: makeEvent<WindowMessageEvent>("window-message-event"),
 };
 
-/* Types */export type BunSidecarOutput = {
+/* Types *//**
+ * Error returned by commands. It serializes as a union tagged by `code`, and
+ * codes are stable, so the frontend can branch on them.
+ */
+export type AppError = ({
+	code: "DbRequestFailed",
+} & AppErrorInfo) | ({
+	code: "CursorPositionUnavailable",
+} & AppErrorInfo) | ({
+	code: "WindowGeometryUnavailable",
+} & AppErrorInfo) | ({
+	code: "MonitorsUnavailable",
+} & AppErrorInfo) | ({
+	code: "WindowMessageUndelivered",
+} & AppErrorInfo) | ({
+	code: "WindowRequestTimedOut",
+} & AppErrorInfo) | ({
+	code: "WindowRequestDropped",
+} & AppErrorInfo) | ({
+	code: "SidecarEntryMissing",
+} & AppErrorInfo) | ({
+	code: "SidecarFailed",
+} & AppErrorInfo) | ({
+	code: "SidecarOutputInvalid",
+} & AppErrorInfo) | ({
+	code: "IoFailed",
+} & AppErrorInfo) | ({
+	code: "NoMessageRecipient",
+} & AppErrorInfo) | ({
+	code: "WindowDescriptorMissing",
+} & AppErrorInfo) | ({
+	code: "WindowBuildFailed",
+} & AppErrorInfo);
+
+/**
+ * Subsystem an error comes from, for handling that does not care about the
+ * exact code.
+ */
+export type AppErrorCategory = "Db" | "Window" | "Sidecar" | "Io" | "Validation";
+
+export type AppErrorInfo = {
+	category: AppErrorCategory,
+	/**
+	 * Translation key of the message shown to the user.
+	 */
+	message_key: string,
+	/**
+	 * The underlying error, for logs and bug reports. It is never localized.
+	 */
+	details: string | null,
+};
+
+export type BunSidecarOutput = {
 	ok: boolean,
 	status: number | null,
 	stdout: string,
@@ -66,12 +163,63 @@ export const events = {
 export type CreateWindowOptions = {
 	width: number | null,
 	height: number | null,
+	placement: MonitorPlacement | null,
 };
 
 export type FullScreenEvent = {
 	is_fullscreen: boolean,
 };
 
+export type GracefulShutdownSnapshot = {
+	/**
+	 * User windows that have not answered the shutdown request yet.
+	 */
+	awaiting_verdict_labels: string[],
+	/**
+	 * Windows that are closing and still have to be destroyed.
+	 */
+	pending_labels: string[],
+};
+
+export type LiveWindowSnapshot = {
+	kind: WindowKindInfo,
+	visible: boolean,
+};
+
+export type MonitorArea = {
+	x: number,
+	y: number,
+	width: number,
+	height: number,
+};
+
+/**
+ * A connected display in physical pixels. `index` is what
+ * `MonitorPlacement::MonitorIndex` expects.
+ */
+export type MonitorInfo = {
+	index: number,
+	name: string | null,
+	bounds: MonitorArea,
+	work_area: MonitorArea,
+	scale_factor: number,
+	is_primary: boolean,
+};
+
+/**
+ * Which monitor `create_window` places a window on.
+ */
+export type MonitorPlacement = {
+	kind: "OpenerMonitor",
+} | {
+	kind: "CursorMonitor",
+} | {
+	kind: "PrimaryMonitor",
+} | {
+	kind: "MonitorIndex",
+	value: number,
+};
+
 export type MouseWindowInfo = {
 	mouse_x: number,
 	mouse_y: number,
@@ -84,15 +232,166 @@ export type MouseWindowInfo = {
 	pixel_ratio: number,
 };
 
+export type OpenedWindow = {
+	kind: WindowKindInfo,
+	source: OpenedWindowSource,
+};
+
+/**
+ * Where the window shown by `create_window` came from.
+ */
+export type OpenedWindowSource = "Prewarmed" | "ColdBuild" | "Existing";
+
+export type PreparedWindowReadiness = "Scheduled" | "Created" | "Ready";
+
+export type PreparedWindowSnapshot = {
+	window: WindowName,
+	label: string,
+	readiness: PreparedWindowReadiness,
+	/**
+	 * Milliseconds spent in the current readiness state.
+	 */
+	readiness_age_ms: number,
+};
+
+export type PrewarmPoolMetrics = {
+	window: WindowName,
+	pool_hits: number,
+	cold_opens: number,
+	recycled: number,
+};
+
+/**
+ * A renderer's answer to `GracefulShutdownRequested`.
+ */
+export type ShutdownVerdict = "Proceed" | "Veto";
+
+export type WarmWindowOwnersSnapshot = {
+	window: WindowName,
+	owners: string[],
+};
+
 export type WindowKindInfo = {
 	window: WindowName | null,
 	label: string,
 	is_primary_window: boolean,
 	is_user_window: boolean,
 	is_prepared_window: boolean,
+	/**
+	 * Window this one was opened from and closes with, for owned kinds.
+	 */
+	owner_label: string | null,
 };
 
-export type WindowName = "Main" | "Support";
+/**
+ * Data handed from `create_window` to the window it opens. The renderer
+ * fetches it once through `take_window_launch_payload`.
+ */
+export type WindowLaunchPayload = {
+	route: string | null,
+	params: { [key in string]: string },
+};
+
+export type WindowLifecycleEvent = {
+	type: "Created",
+	window: WindowName | null,
+	label: string,
+	visible: boolean,
+} | {
+	type: "Prewarmed",
+	window: WindowName,
+	label: string,
+} | {
+	type: "Ready",
+	window: WindowName,
+	label: string,
+} | {
+	type: "Promoted",
+	window: WindowName | null,
+	label: string,
+} | {
+	type: "Destroyed",
+	window: WindowName | null,
+	label: string,
+} | {
+	type: "VisibilityChanged",
+	window: WindowName | null,
+	label: string,
+	visible: boolean,
+} | {
+	type: "GracefulShutdownRequested",
+	request_id: number,
+	closing_label: string | null,
+	awaiting_labels: string[],
+} | {
+	type: "GracefulShutdownCancelled",
+	request_id: number,
+	vetoed_by: string,
+} | {
+	type: "GracefulShutdownStarted",
+	closing_label: string | null,
+	pending_labels: string[],
+};
+
+export type WindowMessageEvent = {
+	id: number,
+	from: string,
+	expects_reply: boolean,
+	payload: WindowMessagePayload,
+};
+
+export type WindowMessagePayload = {
+	type: "Ping",
+} | {
+	type: "Pong",
+} | {
+	type: "Navigate",
+	data: {
+		route: string,
+	},
+} | {
+	type: "Launch",
+	data: WindowLaunchPayload,
+};
+
+export type WindowMessageTarget = {
+	kind: "Label",
+	value: string,
+} | {
+	kind: "Window",
+	value: WindowName,
+} | {
+	kind: "Broadcast",
+};
+
+/**
+ * Window kinds known to the renderer. Adding a kind means adding a variant
+ * here and declaring its descriptor in [`window_descriptors`]; the lifecycle
+ * core in `window.rs` only consumes the registered descriptors.
+ */
+export type WindowName = "Main" | "Support" | "Settings";
+
+/**
+ * Point-in-time copy of the lifecycle state, for diagnosing unexpected
+ * exits and slow window opens.
+ */
+export type WindowRegistrySnapshot = {
+	windows: LiveWindowSnapshot[],
+	prepared_windows: PreparedWindowSnapshot[],
+	warm_window_owners: WarmWindowOwnersSnapshot[],
+	graceful_shutdown: GracefulShutdownSnapshot | null,
+};
+
+/**
+ * Window flags as seen by the window they describe. Sent to that window only,
+ * and only when one of them changed.
+ */
+export type WindowStateEvent = {
+	is_fullscreen: boolean,
+	is_maximized: boolean,
+	is_minimized: boolean,
+	is_focused: boolean,
+};
 
 /* Tauri Specta runtime */
 async function typedError<T, E>(result: Promise<T>): Promise<{ status: "ok"; data: T } | { status: "error"; error: E }> {
@@ -11,6 +11,7 @@ function shouldWarmMain(snapshot: AppBootstrap) {
     snapshot.window.match({
       main: () => true,
       support: () => false,
+      settings: () => false,
     })
   );
 }
@@ -63,6 +63,10 @@ export function inferWindowNameFromLabel(label: string | null | undefined): Wind
     return "support";
   }
 
+  if (label === "settings" || /^settings-\d+$/.test(label)) {
+    return "settings";
+  }
+
   return null;
 }
 
@@ -87,6 +91,7 @@ export function normalizeWindowName(window: CmdWindowName | null | undefined): W
   return me(window).match({
     Main: () => "main",
     Support: () => "support",
+    Settings: () => "settings",
   });
 }
 
@@ -94,6 +99,7 @@ export function toCommandWindowName(window: WindowName): CmdWindowName {
   return me(window).match({
     main: () => "Main",
     support: () => "Support",
+    settings: () => "Settings",
   });
 }
 